@@ -0,0 +1,43 @@
+//! Parsing and both parts of every day. Days having a generator run on a large seeded input, the others on
+//! `inputs/<day>`, or on their example when it is missing.
+
+use aoc_2022::{
+    day01, day02, day03, day04, day05, day06, day07, day08, day09, day10, day11,
+    input::{input_file, read_input},
+    problem::Problem,
+};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const SEED: u64 = 2022;
+
+fn bench_day<T: Problem>(c: &mut Criterion, day: usize, lines: Vec<String>) {
+    let mut group = c.benchmark_group(format!("day{:02}", day));
+    group.bench_function("parse", |b| b.iter(|| T::parse(&lines)));
+    let problem = T::parse(&lines).unwrap();
+    group.bench_function("part_one", |b| b.iter(|| problem.part_one()));
+    group.bench_function("part_two", |b| b.iter(|| problem.part_two()));
+    group.finish();
+}
+
+fn real_input(day: usize) -> Vec<String> {
+    read_input(input_file(day, false))
+        .or_else(|_| read_input(input_file(day, true)))
+        .unwrap()
+}
+
+fn days(c: &mut Criterion) {
+    bench_day::<day01::Inventories>(c, 1, day01::generate(SEED, 10_000));
+    bench_day::<day02::Guide>(c, 2, real_input(2));
+    bench_day::<day03::RuckSacks>(c, 3, real_input(3));
+    bench_day::<day04::AssignmentsPairs>(c, 4, real_input(4));
+    bench_day::<day05::RearrangementProcedure>(c, 5, day05::generate(SEED, 5_000));
+    bench_day::<day06::Signal>(c, 6, real_input(6));
+    bench_day::<day07::FileSystem>(c, 7, day07::generate(SEED, 2_000));
+    bench_day::<day08::Forest>(c, 8, day08::generate(SEED, 200));
+    bench_day::<day09::Movements>(c, 9, day09::generate(SEED, 10_000));
+    bench_day::<day10::Program>(c, 10, real_input(10));
+    bench_day::<day11::MonkeyBehaviors>(c, 11, day11::generate(SEED, 20));
+}
+
+criterion_group!(benches, days);
+criterion_main!(benches);
@@ -0,0 +1,99 @@
+//! Peak memory of `ParseExt::parse_reader` running the moves parser of day 5 over inputs of hundreds of MB. It stays
+//! within what the parsed moves take, however large the input is.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    io::Read,
+    mem::size_of,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use aoc_2022::{parse::seq::SkipFinal, prelude::*};
+
+/// Allocator keeping track of the bytes allocated at once, and of their peak since the last reset.
+struct Counting {
+    current: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let current = self.current.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        self.peak.fetch_max(current, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.current.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting {
+    current: AtomicUsize::new(0),
+    peak: AtomicUsize::new(0),
+};
+
+/// Synthetic input repeating a line until `remaining` bytes were produced.
+struct Repeat {
+    line: &'static [u8],
+    pos: usize,
+    remaining: usize,
+}
+
+impl Read for Repeat {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = buf.len().min(self.remaining);
+        for b in &mut buf[..n] {
+            *b = self.line[self.pos];
+            self.pos = (self.pos + 1) % self.line.len();
+        }
+        self.remaining -= n;
+        Ok(n)
+    }
+}
+
+#[derive(Debug, Parse)]
+#[parse(pattern = "move {count} from {from} to {to}")]
+struct Move {
+    count: usize,
+    from: usize,
+    to: usize,
+}
+
+type Parser = Seq<MoveParser, LineSep, SkipFinal>;
+
+fn main() {
+    // Padded so that a line is longer than the move it gives: a copy of the input wouldn't fit in the bound below.
+    let line = b"move 00000000000000000000000007 from 00000000000000000000000001 to 00000000000000000000000009\n";
+    for megabytes in [100, 200, 400] {
+        let input = Repeat {
+            line,
+            pos: 0,
+            remaining: (megabytes << 20) / line.len() * line.len(),
+        };
+        let baseline = ALLOCATOR.current.load(Ordering::Relaxed);
+        ALLOCATOR.peak.store(baseline, Ordering::Relaxed);
+        let moves = Parser::parse_reader(input).unwrap();
+        let peak = ALLOCATOR.peak.load(Ordering::Relaxed) - baseline;
+
+        // The moves grow by reallocation, the old buffer being freed only once copied into one twice as large.
+        let output = moves.capacity() * size_of::<Move>();
+        let bound = output / 2 * 3 + (64 << 10);
+        println!(
+            "{:>4} MB: {} moves taking {} bytes, peak allocation {} bytes",
+            megabytes,
+            moves.len(),
+            output,
+            peak
+        );
+        assert!(
+            peak <= bound,
+            "Peak allocation of {} bytes beyond the {} bytes of the moves",
+            peak,
+            bound
+        );
+        assert_eq!((moves[0].count, moves[0].from, moves[0].to), (7, 1, 9));
+    }
+}
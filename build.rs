@@ -0,0 +1,47 @@
+use std::{env, fs, path::Path};
+
+/// Registers the `SOLVER` of every `src/dayNN.rs` module, which `lib.rs` declares, in order.
+/// Also generates a test per part of each `inputs/<day>.example` having an answer in `answers/<day>.example.toml`.
+fn main() {
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=inputs");
+    println!("cargo:rerun-if-changed=answers");
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let src = root.join("src");
+    let mut days: Vec<String> = fs::read_dir(&src)
+        .unwrap()
+        .filter_map(|entry| {
+            let name = entry.unwrap().file_name().into_string().ok()?;
+            let stem = name.strip_suffix(".rs")?;
+            let number = stem.strip_prefix("day")?;
+            number.chars().all(|c| c.is_ascii_digit()).then(|| stem.to_string())
+        })
+        .collect();
+    days.sort();
+
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).to_path_buf();
+    let solvers: String = days.iter().map(|day| format!("    crate::{}::SOLVER,\n", day)).collect();
+    fs::write(out.join("solvers.rs"), format!("&[\n{}]\n", solvers)).unwrap();
+
+    let mut tests = String::new();
+    let mut examples: Vec<usize> = fs::read_dir(root.join("inputs"))
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok()?.strip_suffix(".example")?.parse().ok())
+        .collect();
+    examples.sort();
+    for day in examples {
+        let input = root.join("inputs").join(format!("{}.example", day));
+        let answers = root.join("answers").join(format!("{}.example.toml", day));
+        let content = fs::read_to_string(&answers).unwrap_or_default();
+        for part in [1, 2] {
+            if content.lines().any(|line| line.starts_with(&format!("part{}", part))) {
+                tests += &format!(
+                    "#[test]\nfn day{:02}_part{}() {{\n    check({}, {}, {:?}, {:?});\n}}\n\n",
+                    day, part, day, part, input, answers
+                );
+            }
+        }
+    }
+    fs::write(out.join("examples.rs"), tests).unwrap();
+}
@@ -0,0 +1,239 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput, Fields, Ident, LitStr, Type};
+
+/// Derives `<Name>Parser`, a parser whose output is the annotated struct.
+///
+/// The struct is described by a pattern where each `{field}` placeholder is parsed by the field parser, which is
+/// `Natural<FieldType>` unless given with `#[parse(with = "...")]`. The parsed value is converted into the field
+/// type with `Into`.
+///
+/// With `#[parse(pattern = "...", format)]`, the parser also implements `Format`, each field being cloned and
+/// converted back into the output of its parser with `Into`.
+///
+/// ```ignore
+/// #[derive(Parse)]
+/// #[parse(pattern = "move {n} from {from} to {to}")]
+/// struct Move {
+///     n: usize,
+///     from: usize,
+///     to: usize,
+/// }
+///
+/// type Procedure = Seq<MoveParser, LineSep>;
+/// ```
+#[proc_macro_derive(Parse, attributes(parse))]
+pub fn derive_parse(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+struct Field {
+    ident: Ident,
+    parser: Type,
+}
+
+/// Splits `move {n} from {from}` into the capture pattern `move % from %` and the placeholders names.
+fn split_pattern(pattern: &LitStr) -> syn::Result<(String, Vec<String>)> {
+    let mut capture = String::new();
+    let mut names = vec![];
+    let mut chars = pattern.value().chars().collect::<Vec<_>>().into_iter().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                capture.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                capture.push('}');
+            }
+            '{' => {
+                let name: String = chars.by_ref().take_while(|c| *c != '}').collect();
+                if name.is_empty() {
+                    return Err(syn::Error::new(pattern.span(), "Empty placeholder in pattern"));
+                }
+                if capture.ends_with('%') {
+                    return Err(syn::Error::new(pattern.span(), "Placeholders must be separated"));
+                }
+                capture.push('%');
+                names.push(name.trim().to_string());
+            }
+            '%' => return Err(syn::Error::new(pattern.span(), "'%' can't be used in a pattern")),
+            c => capture.push(c),
+        }
+    }
+    Ok((capture, names))
+}
+
+/// Reads `#[parse(pattern = "...")]`, telling whether `format` was also given.
+fn pattern_of(input: &DeriveInput) -> syn::Result<(LitStr, bool)> {
+    let mut pattern = None;
+    let mut format = false;
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("parse")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("pattern") {
+                pattern = Some(meta.value()?.parse::<LitStr>()?);
+                Ok(())
+            } else if meta.path.is_ident("format") {
+                format = true;
+                Ok(())
+            } else {
+                Err(meta.error("Expected `pattern = \"...\"` or `format`"))
+            }
+        })?;
+    }
+    let pattern = pattern.ok_or_else(|| syn::Error::new(input.ident.span(), "Missing #[parse(pattern = \"...\")]"))?;
+    Ok((pattern, format))
+}
+
+fn fields_of(input: &DeriveInput) -> syn::Result<Vec<Field>> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new(
+                    input.ident.span(),
+                    "Parse can only be derived for named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                "Parse can only be derived for structs",
+            ))
+        }
+    };
+    fields
+        .iter()
+        .map(|field| {
+            let ty = &field.ty;
+            let mut parser: Type = syn::parse_quote!(::aoc_2022::parse::natural::Natural<#ty>);
+            for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("parse")) {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("with") {
+                        parser = meta.value()?.parse::<LitStr>()?.parse()?;
+                        Ok(())
+                    } else {
+                        Err(meta.error("Expected `with = \"ParserType\"`"))
+                    }
+                })?;
+            }
+            Ok(Field {
+                ident: field.ident.clone().unwrap(),
+                parser,
+            })
+        })
+        .collect()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            input.generics.span(),
+            "Parse can't be derived for generic structs",
+        ));
+    }
+    let (pattern, format) = pattern_of(&input)?;
+    let (capture_pattern, names) = split_pattern(&pattern)?;
+    let fields = fields_of(&input)?;
+
+    for name in names.iter() {
+        if !fields.iter().any(|field| field.ident == name) {
+            return Err(syn::Error::new(pattern.span(), format!("No field named '{}'", name)));
+        }
+    }
+    for field in fields.iter() {
+        match names.iter().filter(|name| field.ident == name).count() {
+            1 => (),
+            0 => return Err(syn::Error::new(field.ident.span(), "Field missing from the pattern")),
+            _ => {
+                return Err(syn::Error::new(
+                    field.ident.span(),
+                    "Field used more than once in the pattern",
+                ))
+            }
+        }
+    }
+
+    let name = &input.ident;
+    let vis = &input.vis;
+    let label = name.to_string();
+    let parser_ident = format_ident!("{}Parser", name);
+    let pattern_ident = format_ident!("{}Pattern", parser_ident);
+    if names.len() > 8 {
+        return Err(syn::Error::new(pattern.span(), "At most 8 fields can be captured"));
+    }
+    let field_idents: Vec<_> = names
+        .iter()
+        .map(|field_name| &fields.iter().find(|field| field.ident == field_name).unwrap().ident)
+        .collect();
+    let field_parsers: Vec<_> = names
+        .iter()
+        .map(|field_name| &fields.iter().find(|field| field.ident == field_name).unwrap().parser)
+        .collect();
+    let field_names: Vec<_> = field_idents.iter().map(|ident| ident.to_string()).collect();
+    let capture = quote!(::aoc_2022::parse::capture::CaptureT<#pattern_ident, (#(#field_parsers,)*)>);
+    let format_impl = format.then(|| {
+        quote! {
+            impl ::aoc_2022::parse::Format for #parser_ident {
+                fn write(out: &Self::Out, buffer: &mut String) {
+                    let slots: <#capture as ::aoc_2022::parse::Parse>::Out = (#(Clone::clone(&out.#field_idents).into(),)*);
+                    <#capture as ::aoc_2022::parse::Format>::write(&slots, buffer)
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        #[doc(hidden)]
+        #vis struct #pattern_ident {}
+        impl ::aoc_2022::parse::StaticStr for #pattern_ident {
+            fn as_str() -> &'static str {
+                #capture_pattern
+            }
+        }
+
+        #vis struct #parser_ident(#capture);
+
+        impl Default for #parser_ident {
+            fn default() -> Self {
+                Self(Default::default())
+            }
+        }
+
+        impl #parser_ident {
+            /// Names the capture slot a failure happened in after its field, e.g. `Move.from` for `Capture.1`.
+            fn locate(error: ::aoc_2022::parse::Error) -> ::aoc_2022::parse::Error {
+                const FIELDS: &[&str] = &[#(#field_names),*];
+                let slot = error
+                    .outermost()
+                    .and_then(|outermost| outermost.strip_prefix("Capture."))
+                    .and_then(|slot| slot.parse::<usize>().ok());
+                match slot.and_then(|slot| FIELDS.get(slot)) {
+                    Some(field) => error.relabel_outermost(format!("{}.{}", #label, field)),
+                    None => error.within(#label),
+                }
+            }
+        }
+
+        impl ::aoc_2022::parse::Parse for #parser_ident {
+            type Out = #name;
+
+            fn read_byte(&mut self, byte: &u8, context: ::aoc_2022::parse::Context) -> Result<(), ::aoc_2022::parse::Error> {
+                self.0.read_byte(byte, context).map_err(Self::locate)
+            }
+
+            fn end(self, context: ::aoc_2022::parse::Context) -> Result<Self::Out, ::aoc_2022::parse::Error> {
+                let (#(#field_idents,)*) = self.0.end(context).map_err(Self::locate)?;
+                Ok(#name {
+                    #(#field_idents: #field_idents.into()),*
+                })
+            }
+        }
+
+        #format_impl
+    })
+}
@@ -1,27 +1,31 @@
 use itertools::Itertools;
+use serde::Serialize;
 
+use crate::generate::Rng;
 use crate::parse::natural::Natural;
 use crate::parse::separator::{EmptyLineSep, LineSep};
 use crate::parse::seq::Seq;
-use crate::parse::ParseExt;
+use crate::parse::{Format, ParseExt};
 use crate::problem::{self};
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct Inventories(Vec<Vec<usize>>);
 type Parser = Seq<Seq<Natural<usize>, LineSep>, EmptyLineSep>;
+pub const SOLVER: problem::Solver =
+    problem::Solver::new::<Inventories>(1, "Calorie Counting", [problem::AnswerKind::Integer; 2]);
+
 impl problem::Problem for Inventories {
-    fn parse(lines: Vec<String>) -> Result<Self, problem::ParsingError> {
-        let bytes = lines.join("\n");
-        let inventories = Parser::parse(bytes.as_bytes());
+    fn parse(lines: &[String]) -> Result<Self, problem::ParsingError> {
+        let inventories = Parser::parse_lines(lines);
         inventories.map(Self).map_err(Into::into)
     }
 
-    fn part_one(&self) -> Result<String, problem::SolvingError> {
+    fn part_one(&self) -> Result<problem::Answer, problem::SolvingError> {
         let max_inv = self.0.iter().map(|inv| inv.iter().sum::<usize>()).max().unwrap();
-        Ok(max_inv.to_string())
+        Ok(max_inv.into())
     }
 
-    fn part_two(&self) -> Result<String, problem::SolvingError> {
+    fn part_two(&self) -> Result<problem::Answer, problem::SolvingError> {
         let max_inv: usize = self
             .0
             .iter()
@@ -30,6 +34,32 @@ impl problem::Problem for Inventories {
             .rev()
             .take(3)
             .sum();
-        Ok(max_inv.to_string())
+        Ok(max_inv.into())
+    }
+}
+
+/// Seeded input of `size` inventories of 1 to 8 snacks.
+pub fn generate(seed: u64, size: usize) -> Vec<String> {
+    let mut rng = Rng::new(seed);
+    let inventories: Vec<Vec<usize>> = (0..size)
+        .map(|_| (0..rng.range(1..=8)).map(|_| rng.range(1_000..=60_000)).collect())
+        .collect();
+    Parser::format(&inventories).lines().map(String::from).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn it_parses_back_its_formatted_input(
+            inventories in prop::collection::vec(prop::collection::vec(any::<usize>(), 1..10), 1..10)
+        ) {
+            let input = Parser::format(&inventories);
+            prop_assert_eq!(Parser::parse(input.as_bytes()), Ok(inventories));
+        }
     }
 }
@@ -1,13 +1,16 @@
-use std::str::FromStr;
+use std::{fmt::Display, str::FromStr};
+
+use derive_more::Display;
+use serde::Serialize;
 
 use crate::parse::couple::Couple;
 use crate::parse::natural::Natural;
 use crate::parse::separator::{LineSep, SpaceSep};
-use crate::parse::seq::Seq;
+use crate::parse::seq::SeqRecover;
 use crate::parse::ParseExt;
-use crate::problem::{ParsingError, Problem};
+use crate::problem::{Answer, AnswerKind, ParsingError, Problem, Solver};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 enum Concept {
     Rock,
     Papr,
@@ -58,9 +61,19 @@ impl Concept {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 struct Play(Concept);
 
+impl Display for Play {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Concept::Rock => write!(f, "A"),
+            Concept::Papr => write!(f, "B"),
+            Concept::Scis => write!(f, "C"),
+        }
+    }
+}
+
 impl FromStr for Play {
     type Err = String;
 
@@ -74,7 +87,7 @@ impl FromStr for Play {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Display, Serialize)]
 enum Indicator {
     X,
     Y,
@@ -111,9 +124,11 @@ impl MatchResult {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct Guide(Vec<(Play, Indicator)>);
 
+type Parser = SeqRecover<Couple<Natural<Play>, SpaceSep, Natural<Indicator>>, LineSep>;
+
 fn as_play(indicator: Indicator) -> Play {
     match indicator {
         Indicator::X => Play(Concept::Rock),
@@ -130,31 +145,65 @@ fn as_outcome(indicator: Indicator) -> MatchResult {
     }
 }
 
+pub const SOLVER: Solver = Solver::new::<Guide>(2, "Rock Paper Scissors", [AnswerKind::Integer; 2]);
+
 impl Problem for Guide {
-    fn parse(lines: Vec<String>) -> Result<Self, ParsingError> {
-        let guide =
-            Seq::<Couple<Natural<Play>, SpaceSep, Natural<Indicator>>, LineSep>::parse(lines.join("\n").as_bytes())?;
+    fn parse(lines: &[String]) -> Result<Self, ParsingError> {
+        let guide = ParsingError::recovered(Parser::parse_lines(lines)?)?;
         Ok(Self(guide))
     }
 
-    fn part_one(&self) -> Result<String, crate::problem::SolvingError> {
+    fn part_one(&self) -> Result<Answer, crate::problem::SolvingError> {
         let score: usize = self
             .0
             .iter()
             .map(|(o, m)| (o, as_play(*m)))
             .map(|(o, m)| m.0.fight(&o.0).score() + m.0.score())
             .sum();
-        Ok(score.to_string())
+        Ok(score.into())
     }
 
-    fn part_two(&self) -> Result<String, crate::problem::SolvingError> {
+    fn part_two(&self) -> Result<Answer, crate::problem::SolvingError> {
         let score: usize = self
             .0
             .iter()
             .map(|(o, m)| (o, as_outcome(*m)))
             .map(|(o, m)| o.0.has_outcome(m).score() + m.score())
             .sum();
-        Ok(score.to_string())
+        Ok(score.into())
     }
     //
 }
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::parse::Format;
+
+    fn play() -> impl Strategy<Value = Play> {
+        prop_oneof![Just(Concept::Rock), Just(Concept::Papr), Just(Concept::Scis)].prop_map(Play)
+    }
+
+    fn indicator() -> impl Strategy<Value = Indicator> {
+        prop_oneof![Just(Indicator::X), Just(Indicator::Y), Just(Indicator::Z)]
+    }
+
+    #[test]
+    fn it_counts_malformed_rounds() {
+        let lines = |input: &str| input.lines().map(String::from).collect::<Vec<_>>();
+        let err = Guide::parse(&lines("A X\nQ Y\nB Z")).unwrap_err().to_string();
+        assert!(err.starts_with("1 malformed item, first one: 2:1: "), "{}", err);
+        let err = Guide::parse(&lines("A X\nQ Y\nB W")).unwrap_err().to_string();
+        assert!(err.starts_with("2 malformed items, first one: 2:1: "), "{}", err);
+    }
+
+    proptest! {
+        #[test]
+        fn it_parses_back_its_formatted_input(rounds in prop::collection::vec((play(), indicator()), 1..20)) {
+            let input = Parser::format(&(rounds.clone(), vec![]));
+            prop_assert_eq!(Parser::parse(input.as_bytes()), Ok((rounds, vec![])));
+        }
+    }
+}
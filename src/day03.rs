@@ -1,13 +1,14 @@
 use std::collections::HashSet;
 
 use itertools::Itertools;
+use serde::Serialize;
 
-use crate::problem::{self, ParsingError, Problem, SolvingError};
+use crate::problem::{self, Answer, AnswerKind, ParsingError, Problem, Solver, SolvingError};
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct RuckSacks(Vec<RuckSack>);
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 struct RuckSack(Vec<char>, Vec<char>);
 
 impl RuckSack {
@@ -26,7 +27,7 @@ impl RuckSack {
     }
 
     fn item_set(&self) -> HashSet<char> {
-        self.0.clone().into_iter().chain(self.1.clone().into_iter()).collect()
+        self.0.iter().chain(self.1.iter()).copied().collect()
     }
 }
 
@@ -38,12 +39,12 @@ impl<'s> Group<'s> {
         let occurences = self
             .0
             .iter()
-            .map(|sack| sack.item_set().into_iter().collect::<Vec<char>>())
-            .flatten()
+            .flat_map(|sack| sack.item_set().into_iter().collect::<Vec<char>>())
             .counts();
         let badge = occurences
             .into_iter()
-            .filter_map(|(v, c)| (c == 3).then(|| v))
+            .filter(|(_, c)| *c == 3)
+            .map(|(v, _)| v)
             .collect::<Vec<_>>();
         match badge.as_slice() {
             &[badge] => Ok(badge),
@@ -73,14 +74,16 @@ fn priority(c: char) -> Result<usize, SolvingError> {
         .map(|v| v + 1)
 }
 
+pub const SOLVER: Solver = Solver::new::<RuckSacks>(3, "Rucksack Reorganization", [AnswerKind::Integer; 2]);
+
 impl Problem for RuckSacks {
-    fn parse(lines: Vec<String>) -> Result<Self, ParsingError> {
+    fn parse(lines: &[String]) -> Result<Self, ParsingError> {
         let ruck_sacks = lines
-            .into_iter()
-            .map(|mut line| {
+            .iter()
+            .map(|line| {
                 if line.len() % 2 == 0 {
-                    let snd = line.split_off(line.len() / 2);
-                    Ok(RuckSack(line.chars().collect(), snd.chars().collect()))
+                    let (fst, snd) = line.split_at(line.len() / 2);
+                    Ok(RuckSack(fst.chars().collect(), snd.chars().collect()))
                 } else {
                     Err(ParsingError::UnverifiedConstraint(
                         "line '{}' has not even number of items".to_string(),
@@ -91,7 +94,7 @@ impl Problem for RuckSacks {
         Ok(Self(ruck_sacks))
     }
 
-    fn part_one(&self) -> Result<String, problem::SolvingError> {
+    fn part_one(&self) -> Result<Answer, problem::SolvingError> {
         let res: usize = self
             .0
             .iter()
@@ -99,10 +102,10 @@ impl Problem for RuckSacks {
             .collect::<Result<Vec<_>, _>>()?
             .into_iter()
             .sum();
-        Ok(res.to_string())
+        Ok(res.into())
     }
 
-    fn part_two(&self) -> Result<String, problem::SolvingError> {
+    fn part_two(&self) -> Result<Answer, problem::SolvingError> {
         let res: usize = self
             .0
             .iter()
@@ -110,16 +113,11 @@ impl Problem for RuckSacks {
             .into_iter()
             .map(|group| {
                 let group = Group::try_from(group.collect::<Vec<_>>())?;
-                let badge = Group::find_badge(&group);
-                match &badge {
-                    Ok(_) => (),
-                    Err(g) => println!("Group {:?} has err : '{:?}'", group, g),
-                }
-                priority(badge?)
+                priority(Group::find_badge(&group)?)
             })
             .collect::<Result<Vec<_>, _>>()?
             .into_iter()
             .sum();
-        Ok(res.to_string())
+        Ok(res.into())
     }
 }
@@ -1,5 +1,6 @@
 use crate::parse::separator::Dash;
 use crate::parse::separator::Comma;
+use serde::Serialize;
 use std::ops::RangeInclusive;
 
 use crate::{
@@ -7,44 +8,64 @@ use crate::{
         couple::Couple,
         natural::Natural,
         separator::{LineSep, StrSep},
-        seq::Seq,
+        seq::SeqRecover,
         ParseExt,
     },
-    problem::{ParsingError, Problem, SolvingError},
+    problem::{Answer, AnswerKind, ParsingError, Problem, Solver, SolvingError},
 };
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct AssignmentsPairs(Vec<(RangeInclusive<usize>, RangeInclusive<usize>)>);
 
 type RangeParser = Couple<Natural<usize>, StrSep<Dash>, Natural<usize>>;
-type Parser = Seq<Couple<RangeParser, StrSep<Comma>, RangeParser>, LineSep>;
+type Parser = SeqRecover<Couple<RangeParser, StrSep<Comma>, RangeParser>, LineSep>;
 
 fn range_from_couple((start, end): (usize, usize)) -> RangeInclusive<usize> {
     RangeInclusive::new(start, end)
 }
 
+pub const SOLVER: Solver = Solver::new::<AssignmentsPairs>(4, "Camp Cleanup", [AnswerKind::Integer; 2]);
+
 impl Problem for AssignmentsPairs {
-    fn parse(lines: Vec<String>) -> Result<Self, ParsingError> {
-        let ap = Parser::parse(lines.join("\n").as_bytes())?
+    fn parse(lines: &[String]) -> Result<Self, ParsingError> {
+        let ap = ParsingError::recovered(Parser::parse_lines(lines)?)?
             .into_iter()
             .map(|(left_range, right_range)| (range_from_couple(left_range), range_from_couple(right_range)))
             .collect();
         Ok(Self(ap))
     }
 
-    fn part_one(&self) -> Result<String, SolvingError> {
+    fn part_one(&self) -> Result<Answer, SolvingError> {
         let overlaps = self.0.iter().filter(|(left, right)| {
-            (left.contains(&right.start()) && left.contains(&right.end()))
-                || (right.contains(&left.start()) && right.contains(&left.end()))
+            (left.contains(right.start()) && left.contains(right.end()))
+                || (right.contains(left.start()) && right.contains(left.end()))
         });
-        Ok(overlaps.count().to_string())
+        Ok(overlaps.count().into())
     }
 
-    fn part_two(&self) -> Result<String, SolvingError> {
+    fn part_two(&self) -> Result<Answer, SolvingError> {
         let overlaps = self.0.iter().filter(|(left, right)| {
-            (left.contains(&right.start()) || left.contains(&right.end()))
-                || (right.contains(&left.start()) || right.contains(&left.end()))
+            (left.contains(right.start()) || left.contains(right.end()))
+                || (right.contains(left.start()) || right.contains(left.end()))
         });
-        Ok(overlaps.count().to_string())
+        Ok(overlaps.count().into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::parse::Format;
+
+    proptest! {
+        #[test]
+        fn it_parses_back_its_formatted_input(
+            pairs in prop::collection::vec(any::<((usize, usize), (usize, usize))>(), 1..20)
+        ) {
+            let input = Parser::format(&(pairs.clone(), vec![]));
+            prop_assert_eq!(Parser::parse(input.as_bytes()), Ok((pairs, vec![])));
+        }
     }
 }
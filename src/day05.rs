@@ -1,35 +1,39 @@
-use std::str::FromStr;
+use std::{fmt::Debug, fmt::Display, num::NonZeroUsize, str::FromStr};
 
 use itertools::Itertools;
+use serde::Serialize;
 
-use crate::parse::separator::Space;
+use crate::parse::Parse;
 use crate::problem::SolvingError::ExpectationUnfulfilled;
-use crate::parse::StaticStr;
 use crate::{
+    generate::Rng,
     parse::{
-        DefStaticStr,
-        capture::Capture,
+        self,
         couple::Couple,
-        natural::Natural,
-        separator::{EmptyLineSep, LineSep, StrSep},
+        grid::Matrix,
+        separator::{EmptyLineSep, LineSep},
         seq::Seq,
-        table::Table,
-        ParseExt,
+        Context, Format, ParseExt,
     },
-    problem::{ParsingError, Problem, SolvingError},
+    problem::{Answer, AnswerKind, ExtraPart, Params, ParsingError, Problem, Solver, SolvingError},
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Parse, Serialize)]
+#[parse(pattern = "move {n} from {from} to {to}", format)]
 struct Move {
     n: usize,
     from: usize,
     to: usize,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+/// Label of a crate, e.g. `A` for `[A]`, or `AB` for `[AB]`.
+pub type Crate = String;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 enum StackSymbol {
+    #[default]
     Empty,
-    Crate(char),
+    Crate(Crate),
     ColumnIndicator(usize),
 }
 
@@ -42,7 +46,7 @@ impl StackSymbol {
             StackSymbol::ColumnIndicator(_) => 2,
         }
     }
-    fn as_crate_symbol(self) -> Option<char> {
+    fn into_crate_symbol(self) -> Option<Crate> {
         match self {
             StackSymbol::Empty => None,
             StackSymbol::Crate(c) => Some(c),
@@ -53,44 +57,285 @@ impl StackSymbol {
 
 fn is_valid_stack(stack: &[StackSymbol]) -> bool {
     let pattern: Vec<usize> = stack.iter().map(StackSymbol::vertical_group_order).dedup().collect();
-    vec![1] == pattern || vec![0, 1] == pattern
+    // A stack may be empty, while the others are drawn above its index.
+    matches!(pattern.as_slice(), [] | [0] | [1] | [0, 1])
 }
 
 impl FromStr for StackSymbol {
     type Err = String;
 
+    /// Reads a word of the drawing, a crate label being anything but brackets and spaces.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s == "   " {
+        let label = s.strip_prefix('[').and_then(|s| s.strip_suffix(']'));
+        if s.trim().is_empty() {
             Ok(StackSymbol::Empty)
-        } else if s.starts_with("[") && s.ends_with("]") {
-            Ok(StackSymbol::Crate(s.chars().nth(1).unwrap()))
-        } else if s.chars().nth(1).unwrap().is_ascii_digit() {
-            Ok(StackSymbol::ColumnIndicator(usize::from_str(&s[1..=1]).unwrap()))
+        } else if let Some(label) = label.filter(|label| !label.is_empty() && !label.contains(['[', ']', ' '])) {
+            Ok(StackSymbol::Crate(label.to_string()))
+        } else if s.bytes().all(|byte| byte.is_ascii_digit()) {
+            s.parse()
+                .map(StackSymbol::ColumnIndicator)
+                .map_err(|_| format!("'{}' is too large a stack index", s))
         } else {
             Err(format!("'{}' is not a valid stack elements", s))
         }
     }
 }
 
+impl Display for StackSymbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StackSymbol::Empty => Ok(()),
+            StackSymbol::Crate(c) => write!(f, "[{}]", c),
+            StackSymbol::ColumnIndicator(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+/// A word of a line of the drawing, with its first and last bytes both as positions in the line and as contexts.
+struct Word {
+    text: String,
+    first: usize,
+    last: usize,
+    start: Context,
+    end: Context,
+}
+
+impl Word {
+    fn split(line: &[(u8, Context)]) -> Vec<Word> {
+        line.iter()
+            .enumerate()
+            .group_by(|(_, (byte, _))| byte.is_ascii_whitespace())
+            .into_iter()
+            .filter(|(blank, _)| !blank)
+            .map(|(_, bytes)| {
+                let bytes: Vec<_> = bytes.collect();
+                let ((first, (_, start)), (last, (_, end))) = (bytes[0], bytes[bytes.len() - 1]);
+                Word {
+                    text: String::from_utf8_lossy(&bytes.iter().map(|(_, (byte, _))| *byte).collect::<Vec<_>>()).into(),
+                    first,
+                    last,
+                    start: *start,
+                    end: *end,
+                }
+            })
+            .collect()
+    }
+
+    fn error(&self, message: String) -> parse::Error {
+        parse::Error::new(self.start, self.end, message).within("Drawing")
+    }
+
+    fn symbol(&self) -> Result<StackSymbol, parse::Error> {
+        self.text.parse().map_err(|e| self.error(e))
+    }
+}
+
+/// Drawing of the stacks, whose last line numbers them. Each crate belongs to the stack whose index is right below
+/// it, so that indices can have several digits and labels several characters, cells being as wide as needed.
+#[derive(Default)]
+struct StackParser {
+    lines: Vec<Vec<(u8, Context)>>,
+}
+
+impl Parse for StackParser {
+    type Out = Matrix<StackSymbol>;
+
+    fn read_byte(&mut self, byte: &u8, context: Context) -> Result<(), parse::Error> {
+        match (byte, self.lines.last_mut()) {
+            (b'\n', _) => self.lines.push(vec![]),
+            (_, Some(line)) => line.push((*byte, context)),
+            (_, None) => self.lines.push(vec![(*byte, context)]),
+        }
+        Ok(())
+    }
+
+    fn end(mut self, context: Context) -> Result<Self::Out, parse::Error> {
+        let indices = Word::split(&self.lines.pop().unwrap_or_default());
+        if indices.is_empty() {
+            return Err(parse::Error::new(context, context, "No line of stack indices").within("Drawing"));
+        }
+        let mut rows = vec![];
+        for line in &self.lines {
+            let mut row = vec![StackSymbol::Empty; indices.len()];
+            for word in Word::split(line) {
+                let below: Vec<usize> = indices
+                    .iter()
+                    .positions(|index| index.first <= word.last && word.first <= index.last)
+                    .collect();
+                match (word.symbol()?, below.as_slice()) {
+                    (StackSymbol::Crate(_), &[stack]) if row[stack] != StackSymbol::Empty => {
+                        let message = format!("'{}' shares the stack {} with another crate", word.text, stack + 1);
+                        return Err(word.error(message));
+                    }
+                    (symbol @ StackSymbol::Crate(_), &[stack]) => row[stack] = symbol,
+                    (StackSymbol::Crate(_), _) => {
+                        return Err(word.error(format!("'{}' is not right above a single stack index", word.text)))
+                    }
+                    _ => return Err(word.error(format!("'{}' is not a crate", word.text))),
+                }
+            }
+            rows.push(row);
+        }
+        let indices = indices.iter().map(|word| match word.symbol()? {
+            index @ StackSymbol::ColumnIndicator(_) => Ok(index),
+            _ => Err(word.error(format!("'{}' is not a stack index", word.text))),
+        });
+        rows.push(indices.collect::<Result<_, _>>()?);
+        Ok(Matrix::from_rows(rows).expect("every row has a symbol per stack index"))
+    }
+}
+
+impl Format for StackParser {
+    /// Writes each stack in a column as wide as its widest crate, its index lying right after the opening bracket.
+    fn write(out: &Self::Out, buffer: &mut String) {
+        let cell = |symbol: &StackSymbol| match symbol {
+            StackSymbol::ColumnIndicator(n) => format!(" {}", n),
+            symbol => symbol.to_string(),
+        };
+        let widths: Vec<usize> = (0..out.width())
+            .map(|col| out.rows().map(|row| cell(&row[col]).len()).fold(3, usize::max))
+            .collect();
+        let lines: Vec<String> = out
+            .rows()
+            .map(|row| {
+                row.iter()
+                    .zip(&widths)
+                    .map(|(symbol, width)| format!("{:<width$}", cell(symbol), width = width))
+                    .join(" ")
+            })
+            .collect();
+        buffer.push_str(&lines.join("\n"));
+    }
+}
+
+type ProcedureParser = Seq<MoveParser, LineSep>;
+type Parser = Couple<StackParser, EmptyLineSep, ProcedureParser>;
+
+/// Stacks drawn as in the puzzle, bottom crates of each one last, above the line numbering them.
+fn drawing(stacks: &[Vec<Crate>]) -> Matrix<StackSymbol> {
+    let height = stacks.iter().map(Vec::len).max().unwrap_or(0);
+    let rows = (0..height)
+        .rev()
+        .map(|level| {
+            stacks
+                .iter()
+                .map(|stack| stack.get(level).map_or(StackSymbol::Empty, |c| StackSymbol::Crate(c.clone())))
+                .collect()
+        })
+        .chain(std::iter::once((1..=stacks.len()).map(StackSymbol::ColumnIndicator).collect()))
+        .collect();
+    Matrix::from_rows(rows).unwrap()
+}
+
+/// A model of crane, telling how crates taken together from a stack are put down on another one.
+pub trait Crane: Debug {
+    /// Crates put down on the destination stack from bottom to top, given the `lifted` ones as they were stacked.
+    fn unload(&self, lifted: Vec<Crate>) -> Vec<Crate>;
+}
+
+/// Moves crates one at a time, reversing their order.
 #[derive(Debug)]
+pub struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+    fn unload(&self, mut lifted: Vec<Crate>) -> Vec<Crate> {
+        lifted.reverse();
+        lifted
+    }
+}
+
+/// Moves all the crates at once, keeping their order.
+#[derive(Debug)]
+pub struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    fn unload(&self, lifted: Vec<Crate>) -> Vec<Crate> {
+        lifted
+    }
+}
+
+/// Moves at most `capacity` crates at once, topmost first: it behaves as the 9000 with a capacity of 1, and as the
+/// 9001 once its capacity is as high as the stacks.
+#[derive(Debug)]
+pub struct LimitedCrane {
+    pub capacity: NonZeroUsize,
+}
+
+impl Crane for LimitedCrane {
+    fn unload(&self, lifted: Vec<Crate>) -> Vec<Crate> {
+        lifted.rchunks(self.capacity.get()).flatten().cloned().collect()
+    }
+}
+
+/// Crane set by `--param crane=<model>`, the model being `9000`, `9001` or `limited-<capacity>`.
+fn crane(model: &str) -> Result<Box<dyn Crane>, String> {
+    let capacity = model.strip_prefix("limited-").and_then(|capacity| capacity.parse().ok());
+    match (model, capacity) {
+        ("9000", _) => Ok(Box::new(CrateMover9000)),
+        ("9001", _) => Ok(Box::new(CrateMover9001)),
+        (_, Some(capacity)) => Ok(Box::new(LimitedCrane { capacity })),
+        _ => Err(format!("'{}' is not a crane, which are 9000, 9001 and limited-<capacity>", model)),
+    }
+}
+
+#[derive(Debug, Serialize)]
 pub struct RearrangementProcedure {
-    stacks: Vec<Vec<char>>,
+    stacks: Vec<Vec<Crate>>,
     moves: Vec<Move>,
+    /// Crane of the replay.
+    #[serde(skip)]
+    crane: Box<dyn Crane>,
 }
-DefStaticStr!(MoveFromTo, "move % from % to %");
+
+impl RearrangementProcedure {
+    /// Stacks once `crane` has done every move, `step` being given the index of each move along with the stacks it
+    /// leads to.
+    pub fn rearrange(
+        &self,
+        crane: &dyn Crane,
+        mut step: impl FnMut(usize, &[Vec<Crate>]),
+    ) -> Result<Vec<Vec<Crate>>, SolvingError> {
+        let mut stacks = self.stacks.clone();
+        for (index, m) in self.moves.iter().enumerate() {
+            let height = stacks[m.from - 1].len();
+            if height < m.n {
+                return Err(ExpectationUnfulfilled(
+                    "You are trying to pull a crate from an empty stack budy !".into(),
+                ));
+            }
+            let lifted = stacks[m.from - 1].split_off(height - m.n);
+            stacks[m.to - 1].extend(crane.unload(lifted));
+            step(index, &stacks);
+        }
+        Ok(stacks)
+    }
+
+    fn top_crates(stacks: Vec<Vec<Crate>>) -> Answer {
+        stacks.iter().filter_map(|stack| stack.last()).join("").into()
+    }
+
+    /// The drawing of the stacks, then again after each move, for the crane set with `--param crane=<model>`.
+    fn replay(&self) -> Result<Answer, SolvingError> {
+        let mut steps = vec![StackParser::format(&drawing(&self.stacks))];
+        self.rearrange(self.crane.as_ref(), |index, stacks| {
+            let m = MoveParser::format(&self.moves[index]);
+            steps.push(format!("{}\n{}", m, StackParser::format(&drawing(stacks))))
+        })?;
+        Ok(Answer::Text(steps.join("\n\n")))
+    }
+}
+
+pub const SOLVER: Solver = Solver::new::<RearrangementProcedure>(5, "Supply Stacks", [AnswerKind::Text; 2]);
 
 impl Problem for RearrangementProcedure {
-    fn parse(lines: Vec<String>) -> Result<Self, ParsingError> {
-        type StackParser = Seq<Table<3, StrSep<Space>, Natural<StackSymbol>>, LineSep>;
-        type ProcedureParser = Seq<Capture<MoveFromTo, 3, Natural<usize>>, LineSep>;
-        let res = Couple::<StackParser, EmptyLineSep, ProcedureParser>::parse(lines.join("\n").as_bytes())?;
-        let mut stacks_rows = res.0;
+    fn parse(lines: &[String]) -> Result<Self, ParsingError> {
+        Self::parse_with(lines, &Params::default())
+    }
 
-        if !stacks_rows.iter().map(|hor| hor.len()).all_equal() {
-            return Err(ParsingError::UnverifiedConstraint(
-                "The drawing has lines with different number of stack".into(),
-            ));
-        }
+    fn parse_with(lines: &[String], params: &Params) -> Result<Self, ParsingError> {
+        let crane = crane(params.get("crane").unwrap_or("9000")).map_err(ParsingError::UnverifiedConstraint)?;
+        let res = Parser::parse_lines(lines)?;
+        let mut stacks_rows = res.0.into_rows();
 
         let column_line = stacks_rows.pop().ok_or(ParsingError::UnverifiedConstraint(
             "Their is not even a columns indicators line".into(),
@@ -107,16 +352,7 @@ impl Problem for RearrangementProcedure {
             ));
         }
 
-        if !column_line
-            .iter()
-            .all(|symbol| matches!(symbol, StackSymbol::ColumnIndicator(_)))
-        {
-            return Err(ParsingError::UnverifiedConstraint(
-                "Last line must be indicating columns".into(),
-            ));
-        }
-
-        let stacks: Vec<Vec<StackSymbol>> = (0..stacks_rows[0].len()).map(|_| Vec::new()).collect();
+        let stacks: Vec<Vec<StackSymbol>> = (0..column_line.len()).map(|_| Vec::new()).collect();
 
         let stacks = stacks_rows.into_iter().fold(stacks, |mut acc, row| {
             row.into_iter().enumerate().for_each(|(col, symbol)| {
@@ -136,59 +372,187 @@ impl Problem for RearrangementProcedure {
             .map(|stack| {
                 stack
                     .into_iter()
-                    .filter_map(StackSymbol::as_crate_symbol)
+                    .filter_map(StackSymbol::into_crate_symbol)
                     .rev()
                     .collect()
             })
             .collect();
 
-        let moves: Vec<_> = res.1.into_iter().map(|[n, from, to]| Move { n, from, to }).collect();
+        let moves = res.1;
 
-        if let Some(_) = moves.iter().find(|order| !(1..=stacks.len()).contains(&order.from)) {
+        if moves.iter().any(|order| !(1..=stacks.len()).contains(&order.from)) {
             return Err(ParsingError::UnverifiedConstraint(
                 "Oh noooo! you grabbed an elf which was standing near the crates stacks".into(),
             ));
         }
 
-        if let Some(_) = moves.iter().find(|order| !(1..=stacks.len()).contains(&order.to)) {
+        if moves.iter().any(|order| !(1..=stacks.len()).contains(&order.to)) {
             return Err(ParsingError::UnverifiedConstraint(
                 "Oh noooo! You throwned a crate away of the ship !".into(),
             ));
         }
 
-        Ok(Self { stacks, moves })
+        Ok(Self { stacks, moves, crane })
     }
 
-    fn part_one(&self) -> Result<String, SolvingError> {
-        let mut stacks = self.stacks.clone();
-        for m in self.moves.iter() {
-            for _ in 1..=m.n {
-                match stacks[m.from - 1].pop() {
-                    Some(cra) => stacks[m.to - 1].push(cra),
-                    None => {
-                        return Err(ExpectationUnfulfilled(
-                            "You are trying to pull a crate from an empty stack budy !".into(),
-                        ))
-                    }
-                }
-            }
-        }
-        Ok(stacks.iter().map(|stack| stack.last()).flatten().join(""))
+    fn part_one(&self) -> Result<Answer, SolvingError> {
+        self.rearrange(&CrateMover9000, |_, _| ()).map(Self::top_crates)
     }
 
-    fn part_two(&self) -> Result<String, SolvingError> {
-        let mut stacks = self.stacks.clone();
-        for m in self.moves.iter() {
-            let remaining_crates = stacks[m.from - 1].len() as isize - m.n as isize;
-            if remaining_crates >= 0 {
-                let mut moved = stacks[m.from - 1].split_off(remaining_crates as usize);
-                stacks[m.to - 1].append(&mut moved);
-            } else {
-                return Err(ExpectationUnfulfilled(
-                    "You are trying to pull a crate from an empty stack budy !".into(),
-                ));
+    fn part_two(&self) -> Result<Answer, SolvingError> {
+        self.rearrange(&CrateMover9001, |_, _| ()).map(Self::top_crates)
+    }
+
+    fn params() -> Vec<(&'static str, &'static str)> {
+        vec![("crane", "model of the crane replayed, 9000 by default, 9001 or limited-<capacity>")]
+    }
+
+    fn extra_parts() -> Vec<ExtraPart<Self>> {
+        vec![ExtraPart {
+            name: "replay",
+            description: "the drawing of the stacks after each move",
+            run: Self::replay,
+        }]
+    }
+}
+
+/// Seeded input of nine stacks of 1 to `size` crates, rearranged by `size` moves that never take more crates than a
+/// stack holds.
+pub fn generate(seed: u64, size: usize) -> Vec<String> {
+    let mut rng = Rng::new(seed);
+    let mut stacks: Vec<Vec<Crate>> = (0..9)
+        .map(|_| {
+            (0..rng.range(1..=size.max(1)))
+                .map(|_| ((b'A' + rng.range(0..=25) as u8) as char).to_string())
+                .collect()
+        })
+        .collect();
+
+    let rows = drawing(&stacks);
+
+    let moves = (0..size)
+        .map(|_| {
+            let filled: Vec<usize> = (0..9).filter(|stack| !stacks[*stack].is_empty()).collect();
+            let from = *rng.choose(&filled);
+            let to = (from + rng.range(1..=8)) % 9;
+            let height = stacks[from].len();
+            let n = rng.range(1..=height);
+            let moved = stacks[from].split_off(height - n);
+            stacks[to].extend(moved);
+            Move {
+                n,
+                from: from + 1,
+                to: to + 1,
             }
+        })
+        .collect();
+
+    let input = Parser::format(&(rows, moves));
+    input.lines().map(String::from).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn example() -> RearrangementProcedure {
+        let lines: Vec<String> = include_str!("../inputs/5.example").lines().map(String::from).collect();
+        RearrangementProcedure::parse(&lines).unwrap()
+    }
+
+    #[test]
+    fn it_rearranges_with_any_crane() {
+        let procedure = example();
+        let top_crates = |crane: &dyn Crane| {
+            procedure.rearrange(crane, |_, _| ()).map(RearrangementProcedure::top_crates)
+        };
+        let limited = |capacity| LimitedCrane {
+            capacity: NonZeroUsize::new(capacity).unwrap(),
+        };
+        assert_eq!(top_crates(&limited(1)).unwrap(), top_crates(&CrateMover9000).unwrap());
+        assert_eq!(top_crates(&limited(3)).unwrap(), top_crates(&CrateMover9001).unwrap());
+        let lifted = ["a", "b", "c", "d", "e"].map(String::from).to_vec();
+        assert_eq!(limited(2).unload(lifted), vec!["d", "e", "b", "c", "a"]);
+    }
+
+    #[test]
+    fn it_replays_the_drawing_move_by_move() {
+        let Answer::Text(replay) = example().replay().unwrap() else {
+            panic!("a replay is text")
+        };
+        let steps: Vec<&str> = replay.split("\n\n").collect();
+        assert_eq!(steps.len(), 5);
+        assert_eq!(steps[0], "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 ");
+        assert_eq!(steps[1], "move 1 from 2 to 1\n[D]        \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 ");
+
+        let params = [("crane".to_string(), "limited-0".to_string())].into_iter().collect();
+        let lines: Vec<String> = include_str!("../inputs/5.example").lines().map(String::from).collect();
+        assert!(RearrangementProcedure::parse_with(&lines, &params).is_err());
+    }
+
+    #[test]
+    fn it_reads_wide_drawings_by_the_position_of_indices() {
+        let input = [
+            "                                           [XY]",
+            "[A]                                 [J]    [KL]",
+            "[B] [C] [D] [E] [F] [G] [H] [I] [IJ] [K]   [M] ",
+            " 1   2   3   4   5   6   7   8   9    10   11  ",
+            "",
+            "move 2 from 11 to 1",
+        ];
+        let procedure = RearrangementProcedure::parse(&input.map(String::from)).unwrap();
+        assert_eq!(procedure.stacks.len(), 11);
+        assert_eq!(procedure.stacks[9], vec!["K", "J"]);
+        assert_eq!(procedure.part_one().unwrap(), Answer::Text("KLCDEFGHIIJJM".into()));
+
+        let stacks = vec![vec!["ABC".to_string()], vec![], vec!["D".to_string(); 3]];
+        let input = StackParser::format(&drawing(&stacks));
+        assert_eq!(input, "          [D]\n          [D]\n[ABC]     [D]\n 1     2   3 ");
+        assert_eq!(StackParser::parse(input.as_bytes()), Ok(drawing(&stacks)));
+    }
+
+    #[test]
+    fn it_locates_crates_it_cannot_place() {
+        let error = |drawing: &str| StackParser::parse(drawing.as_bytes()).unwrap_err().to_string();
+        assert_eq!(error("[AB]\n 1 2"), "1:1: Drawing: '[AB]' is not right above a single stack index");
+        assert_eq!(error("      [A]\n 1  2"), "1:7: Drawing: '[A]' is not right above a single stack index");
+        assert_eq!(error("[A\n 1"), "1:1: Drawing: '[A' is not a valid stack elements");
+        assert_eq!(error("[A]\n 1 x"), "2:4: Drawing: 'x' is not a valid stack elements");
+        assert_eq!(error("[A]\n[B]"), "2:1: Drawing: '[B]' is not a stack index");
+        assert_eq!(error(" 2\n 1"), "1:2: Drawing: '2' is not a crate");
+    }
+
+    fn stack_symbol() -> impl Strategy<Value = StackSymbol> {
+        prop_oneof![Just(StackSymbol::Empty), "[A-Z]{1,3}".prop_map(StackSymbol::Crate)]
+    }
+
+    /// Rows of crates above a line of indices, as wide as each other.
+    fn drawing_rows() -> impl Strategy<Value = Vec<Vec<StackSymbol>>> {
+        (1..15usize).prop_flat_map(|width| {
+            let indices = prop::collection::vec((1..=999usize).prop_map(StackSymbol::ColumnIndicator), width);
+            (prop::collection::vec(prop::collection::vec(stack_symbol(), width), 0..10), indices)
+                .prop_map(|(mut rows, indices)| {
+                    rows.push(indices);
+                    rows
+                })
+        })
+    }
+
+    fn a_move() -> impl Strategy<Value = Move> {
+        any::<(usize, usize, usize)>().prop_map(|(n, from, to)| Move { n, from, to })
+    }
+
+    proptest! {
+        #[test]
+        fn it_parses_back_its_formatted_input(
+            rows in drawing_rows(),
+            moves in prop::collection::vec(a_move(), 1..20),
+        ) {
+            let rows = Matrix::from_rows(rows).unwrap();
+            let input = Parser::format(&(rows.clone(), moves.clone()));
+            prop_assert_eq!(Parser::parse(input.as_bytes()), Ok((rows, moves)));
         }
-        Ok(stacks.iter().map(|stack| stack.last()).flatten().join(""))
     }
 }
@@ -1,8 +1,9 @@
 use itertools::Itertools;
+use serde::Serialize;
 
-use crate::problem::{ParsingError, Problem, SolvingError};
+use crate::problem::{Answer, AnswerKind, ParsingError, Problem, Solver, SolvingError};
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct Signal(String);
 
 impl Signal {
@@ -17,24 +18,27 @@ impl Signal {
     }
 }
 
+pub const SOLVER: Solver = Solver::new::<Signal>(6, "Tuning Trouble", [AnswerKind::Integer; 2]);
+
 impl Problem for Signal {
-    fn parse(mut lines: Vec<String>) -> Result<Self, ParsingError> {
+    fn parse(lines: &[String]) -> Result<Self, ParsingError> {
         lines
-            .pop()
+            .last()
+            .cloned()
             .ok_or_else(|| ParsingError::UnverifiedConstraint("No signal at all received (empty file) !".into()))
             .map(Self)
     }
 
-    fn part_one(&self) -> Result<String, SolvingError> {
+    fn part_one(&self) -> Result<Answer, SolvingError> {
         match self.find_start_marker_pos(4) {
-            Some(ind) => Ok((ind + 4).to_string()),
+            Some(ind) => Ok((ind + 4).into()),
             None => Err(SolvingError::ExpectationUnfulfilled("No packet start detected".into())),
         }
     }
 
-    fn part_two(&self) -> Result<String, SolvingError> {
+    fn part_two(&self) -> Result<Answer, SolvingError> {
         match self.find_start_marker_pos(14) {
-            Some(ind) => Ok((ind + 14).to_string()),
+            Some(ind) => Ok((ind + 14).into()),
             None => Err(SolvingError::ExpectationUnfulfilled("No packet start detected".into())),
         }
     }
@@ -1,58 +1,112 @@
-use crate::parse::StaticStr;
-use std::str::FromStr;
+use serde::Serialize;
+use std::{fmt::Display, str::FromStr};
+use strum_macros::{Display, EnumString};
 
+use crate::generate::Rng;
 use crate::parse::couple::SplitFirst;
 use crate::parse::natural::Natural;
 use crate::parse::seq::{Skip, SkipFinal};
 use crate::parse::DefStaticStr;
 use crate::{
     parse::{
+        self,
         couple::Couple,
+        located::Located,
         separator::{LineSep, StrSep},
         seq::Seq,
-        ParseExt,
+        Context, ParseExt,
     },
-    problem::{ParsingError, Problem, SolvingError},
+    problem::{Answer, AnswerKind, ExtraPart, Params, ParsingError, Problem, Solver, SolvingError},
 };
 
 use itertools::Itertools;
 
-#[allow(dead_code)]
-#[derive(Debug)]
-struct File {
+#[derive(Debug, Serialize)]
+pub struct File {
     size: usize,
     name: String,
 }
 
-#[derive(Debug)]
-struct Directory {
+impl File {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+/// A directory, with its entries in the order they were listed. `total` is the size of everything it holds, computed
+/// once the whole transcript is read.
+#[derive(Debug, Serialize)]
+pub struct Directory {
     name: String,
     browsed: bool,
     subdirectories: Vec<Directory>,
     files: Vec<File>,
+    total: usize,
 }
 
 impl Directory {
-    fn root() -> Self {
-        Directory {
-            name: "/".to_string(),
-            browsed: false,
-            subdirectories: vec![],
-            files: vec![],
-        }
-    }
-    fn unbrowsed(name: String) -> Self {
+    fn new(name: String) -> Self {
         Self {
             name,
             browsed: false,
             subdirectories: vec![],
             files: vec![],
+            total: 0,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn subdirectories(&self) -> &[Directory] {
+        &self.subdirectories
+    }
+
+    pub fn files(&self) -> &[File] {
+        &self.files
+    }
+
+    pub fn total_size(&self) -> usize {
+        self.total
+    }
+
+    fn subdirectory(&self, name: &str) -> Option<&Directory> {
+        self.subdirectories.iter().find(|dir| dir.name == name)
+    }
+
+    fn subdirectory_mut(&mut self, name: &str) -> Option<&mut Directory> {
+        self.subdirectories.iter_mut().find(|dir| dir.name == name)
+    }
+
+    /// Replaces the entries by the listed ones, a subdirectory listed again keeping what was already known of it.
+    fn list(&mut self, entries: Vec<CommandAnswerItem>) {
+        let mut known = std::mem::take(&mut self.subdirectories);
+        self.files.clear();
+        for entry in entries {
+            match entry {
+                CommandAnswerItem::FileDesc { size, name } => self.files.push(File { size, name }),
+                CommandAnswerItem::DirDesc(name) => {
+                    let dir = match known.iter().position(|dir| dir.name == name) {
+                        Some(index) => known.remove(index),
+                        None => Directory::new(name),
+                    };
+                    self.subdirectories.push(dir);
+                }
+                CommandAnswerItem::Other(_) => unreachable!("ls outputs are checked to only list entries"),
+            }
         }
+        self.browsed = true;
     }
 
-    fn total_size(&self) -> usize {
-        self.files.iter().map(|file| file.size).sum::<usize>()
-            + self.subdirectories.iter().map(|dir| dir.total_size()).sum::<usize>()
+    fn compute_totals(&mut self) -> usize {
+        self.total = self.files.iter().map(|file| file.size).sum::<usize>()
+            + self.subdirectories.iter_mut().map(Directory::compute_totals).sum::<usize>();
+        self.total
     }
 
     fn total_sum_of_subdirectories(&self, maximal_size: usize) -> usize {
@@ -73,164 +127,311 @@ impl Directory {
         let others = self
             .subdirectories
             .iter()
-            .map(|dir| dir.smallest_dir_with_size_over(minimal_size))
-            .flatten()
+            .filter_map(|dir| dir.smallest_dir_with_size_over(minimal_size))
             .min();
-        [my, others].iter().flatten().min().map(|e| *e)
+        [my, others].iter().flatten().min().copied()
     }
+
+    /// Writes the total size of the directory found at `path` after the ones of its subdirectories, as `du` does.
+    fn write_du(&self, path: &str, sizes: &mut Vec<(String, usize)>) {
+        for dir in &self.subdirectories {
+            dir.write_du(&join(path, &dir.name), sizes);
+        }
+        sizes.push((path.to_string(), self.total));
+    }
+
+    /// Writes the directory as in the puzzle statement, one indented line per entry.
+    fn write_tree(&self, depth: usize, lines: &mut Vec<String>) {
+        lines.push(format!("{}- {} (dir)", "  ".repeat(depth), self.name));
+        for dir in &self.subdirectories {
+            dir.write_tree(depth + 1, lines);
+        }
+        for file in &self.files {
+            lines.push(format!("{}- {} (file, size={})", "  ".repeat(depth + 1), file.name, file.size));
+        }
+    }
+}
+
+/// Absolute path of the entry `name` of the directory found at `parent`.
+fn join(parent: &str, name: &str) -> String {
+    match parent {
+        "/" => format!("/{}", name),
+        _ => format!("{}/{}", parent, name),
+    }
+}
+
+/// Absolute path of the directory reached from the root through `names`.
+fn path(names: &[String]) -> String {
+    names.iter().fold("/".to_string(), |path, name| join(&path, name))
 }
 
 #[derive(Debug)]
-pub struct FileSystem(Directory);
+pub enum Entry<'a> {
+    Directory(&'a Directory),
+    File(&'a File),
+}
+
+/// The browsed directory tree, along with the unknown commands skipped with a warning.
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
+pub struct FileSystem(Directory, #[serde(skip)] Vec<parse::Error>);
+
+impl FileSystem {
+    /// Entry at the absolute `path`, e.g. `/a/e` for a directory or `/a/e/i` for a file.
+    pub fn get(&self, path: &str) -> Option<Entry<'_>> {
+        let mut names = path.strip_prefix('/')?.split('/').filter(|name| !name.is_empty()).peekable();
+        let mut dir = &self.0;
+        while let Some(name) = names.next() {
+            match (dir.subdirectory(name), names.peek()) {
+                (Some(subdirectory), _) => dir = subdirectory,
+                (None, None) => return dir.files.iter().find(|file| file.name == name).map(Entry::File),
+                (None, Some(_)) => return None,
+            }
+        }
+        Some(Entry::Directory(dir))
+    }
+
+    /// Total size of every directory by absolute path, subdirectories coming before their parent as with `du`.
+    pub fn du(&self) -> Vec<(String, usize)> {
+        let mut sizes = vec![];
+        self.0.write_du("/", &mut sizes);
+        sizes
+    }
 
+    fn disk_usage(&self) -> Result<Answer, SolvingError> {
+        let lines = self.du().into_iter().map(|(path, size)| format!("{}\t{}", size, path)).join("\n");
+        Ok(Answer::Text(lines))
+    }
+
+    fn tree(&self) -> Result<Answer, SolvingError> {
+        let mut lines = vec![];
+        self.0.write_tree(0, &mut lines);
+        Ok(Answer::Text(lines.join("\n")))
+    }
+}
+
+/// What to do with a command other than `cd`, `ls` and `pwd`, set with `--param unknown-commands=<policy>`.
+#[derive(EnumString, Display, Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[strum(serialize_all = "lowercase")]
+pub enum UnknownCommands {
+    Skip,
+    /// Skips it, raising a warning with its line.
+    Warn,
+    #[default]
+    Fail,
+}
+
+/// Replays a transcript, the current directory being known by the names leading to it from the root.
 struct FileSystemBuilder {
-    current_path: Vec<usize>,
+    cwd: Vec<String>,
     root: Directory,
+    unknown_commands: UnknownCommands,
+    warnings: Vec<parse::Error>,
 }
 
 impl FileSystemBuilder {
-    fn new() -> Self {
+    fn new(unknown_commands: UnknownCommands) -> Self {
         Self {
-            current_path: vec![],
-            root: Directory::root(),
+            cwd: vec![],
+            root: Directory::new("/".to_string()),
+            unknown_commands,
+            warnings: vec![],
         }
     }
 
-    fn interpret_command(&mut self, (command, cmd_answers): (Command, Vec<CommandAnswerItem>)) -> Result<(), String> {
-        use Command::*;
-        use CommandAnswerItem::*;
-
-        match (command, cmd_answers.as_slice()) {
-            (Cd(dir), &[]) if &dir == "/" => Ok(self.current_path.clear()),
-            (Cd(dir), &[]) if &dir == ".." => {
-                self.current_path.pop();
-                Ok(())
+    /// Replays a command, failing with the span of the line at fault.
+    fn interpret_command(
+        &mut self,
+        ((command, start, end), cmd_answers): (Spanned<Command>, Vec<Spanned<CommandAnswerItem>>),
+    ) -> Result<(), parse::Error> {
+        let located = |message: String| parse::Error::new(start, end, message).within("Transcript");
+        match (command, cmd_answers.is_empty()) {
+            (Command::Cd(path), true) => self.cd(&path).map_err(located)?,
+            (Command::Ls, _) => {
+                if let Some((line, start, end)) = cmd_answers.iter().find(|(item, _, _)| item.is_other()) {
+                    let message = format!("'{}' is neither a directory nor a file", line);
+                    return Err(parse::Error::new(*start, *end, message).within("Transcript"));
+                }
+                self.current_dir().list(cmd_answers.into_iter().map(|(item, _, _)| item).collect())
             }
-            (Cd(dir), &[]) => {
-                let (new_pos, _) = self
-                    .get_dir(&self.current_path)?
-                    .subdirectories
-                    .iter()
-                    .find_position(|d| d.name == dir)
-                    .ok_or_else(|| format!("'{}' :wrong dir", dir))?;
-                self.current_path.push(new_pos);
-                Ok(())
-            }
-            (Ls, &[]) => {
-                self.current_dir()?.browsed = true;
-                self.current_dir()?.subdirectories.clear();
-                self.current_dir()?.files.clear();
-                Ok(())
+            (Command::Pwd, _) => {
+                let shown = cmd_answers.iter().map(|(line, _, _)| line.to_string()).join("\n");
+                if !cmd_answers.is_empty() && shown != self.pwd() {
+                    return Err(located(format!("pwd shows '{}' in {}", shown, self.pwd())));
+                }
             }
+            (Command::Cd(path), false) => return Err(located(format!("cd {} has an output", path))),
+            (Command::Other(command), _) => match self.unknown_commands {
+                UnknownCommands::Skip => (),
+                UnknownCommands::Warn => self
+                    .warnings
+                    .push(located(format!("unknown command '{}', skipped", command))),
+                UnknownCommands::Fail => {
+                    let hint = "use --param unknown-commands=skip or warn to go on";
+                    return Err(located(format!("unknown command '{}', {}", command, hint)));
+                }
+            },
+        }
+        Ok(())
+    }
 
-            (Ls, file_entries) => {
-                let (files, dirs) = file_entries
-                    .into_iter()
-                    .fold((vec![], vec![]), |(mut files, mut dirs), fl| {
-                        match fl {
-                            FileDesc { size, name } => files.push(File {
-                                size: *size,
-                                name: name.to_owned(),
-                            }),
-                            DirDesc(name) => dirs.push(Directory::unbrowsed(name.to_owned())),
-                        }
-                        (files, dirs)
-                    });
-                self.current_dir()?.browsed = true;
-                self.current_dir()?.subdirectories = dirs;
-                self.current_dir()?.files = files;
-
-                Ok(())
+    /// Moves along `path`, from the root when it starts with `/`, checking that every directory entered is listed.
+    fn cd(&mut self, path: &str) -> Result<(), String> {
+        let mut cwd = match path.starts_with('/') {
+            true => vec![],
+            false => self.cwd.clone(),
+        };
+        for name in path.split('/') {
+            match name {
+                "" | "." => (),
+                ".." => {
+                    cwd.pop().ok_or("cd .. at the root, which has no parent")?;
+                }
+                name => {
+                    if self.dir(&cwd).subdirectory(name).is_none() {
+                        return Err(format!("cd into '{}', which isn't listed in {}", name, self::path(&cwd)));
+                    }
+                    cwd.push(name.to_string());
+                }
             }
-            _ => Err("Invalid cmd".to_string()),
         }
+        self.cwd = cwd;
+        Ok(())
     }
 
-    fn get_dir_mut(&mut self, path: &[usize]) -> Result<&mut Directory, String> {
-        path.into_iter().fold(Ok(&mut self.root), |curr_dir, path_item| {
-            curr_dir?
-                .subdirectories
-                .get_mut(*path_item)
-                .ok_or_else(|| format!("Did not find"))
+    fn pwd(&self) -> String {
+        path(&self.cwd)
+    }
+
+    fn dir(&self, names: &[String]) -> &Directory {
+        names.iter().fold(&self.root, |dir, name| {
+            dir.subdirectory(name).expect("cd only enters listed directories")
         })
     }
 
-    fn get_dir(&self, path: &[usize]) -> Result<&Directory, String> {
-        path.into_iter().fold(Ok(&self.root), |curr_dir, path_item| {
-            curr_dir?.subdirectories.get(*path_item).ok_or_else(|| "".to_string())
+    fn current_dir(&mut self) -> &mut Directory {
+        self.cwd.iter().fold(&mut self.root, |dir, name| {
+            dir.subdirectory_mut(name).expect("cd only enters listed directories")
         })
     }
 
-    fn current_dir(&mut self) -> Result<&mut Directory, String> {
-        let slice = self.current_path.clone();
-        self.get_dir_mut(&slice)
+    fn build(mut self) -> FileSystem {
+        self.root.compute_totals();
+        FileSystem(self.root, self.warnings)
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 enum Command {
+    /// Relative or absolute path, e.g. `a/b`, `../c` or `/d/e`.
     Cd(String),
     Ls,
+    Pwd,
+    /// Any other command, handled as set by `UnknownCommands`.
+    Other(String),
 }
 
 impl FromStr for Command {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(match s.split(" ").collect::<Vec<_>>().as_slice() {
-            &["cd", name] => Command::Cd(name.to_string()),
-            &["ls"] => Command::Ls,
-            _ => Err(format!("'{}' is not a valid command", s))?,
+        Ok(match *s.split(' ').collect::<Vec<_>>().as_slice() {
+            ["cd", path] => Command::Cd(path.to_string()),
+            ["ls"] => Command::Ls,
+            ["pwd"] => Command::Pwd,
+            [""] | ["cd", ..] => Err(format!("'{}' is not a valid command", s))?,
+            _ => Command::Other(s.to_string()),
         })
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 enum CommandAnswerItem {
     FileDesc { size: usize, name: String },
     DirDesc(String),
+    /// A line of another output than the one of `ls`, like the path shown by `pwd`.
+    Other(String),
     //ImplicitOk,
 }
 
+impl CommandAnswerItem {
+    fn is_other(&self) -> bool {
+        matches!(self, CommandAnswerItem::Other(_))
+    }
+}
+
 impl FromStr for CommandAnswerItem {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(match s.split(" ").collect::<Vec<_>>().as_slice() {
-            &["dir", name] => CommandAnswerItem::DirDesc(name.to_string()),
-            &[size, name] => CommandAnswerItem::FileDesc {
-                size: size.parse().map_err(|_| "Invalid file size".to_string())?,
-                name: name.to_string(),
+        Ok(match *s.split(' ').collect::<Vec<_>>().as_slice() {
+            ["dir", name] => CommandAnswerItem::DirDesc(name.to_string()),
+            [size, name] => match size.parse() {
+                Ok(size) => CommandAnswerItem::FileDesc {
+                    size,
+                    name: name.to_string(),
+                },
+                Err(_) => CommandAnswerItem::Other(s.to_string()),
             },
             //       &[""] => CommandAnswerItem::ImplicitOk,
-            _ => Err(format!("'{}' is not a valid command answer", s))?,
+            _ => CommandAnswerItem::Other(s.to_string()),
         })
     }
 }
 
+impl Display for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Command::Cd(path) => write!(f, "cd {}", path),
+            Command::Ls => write!(f, "ls"),
+            Command::Pwd => write!(f, "pwd"),
+            Command::Other(command) => write!(f, "{}", command),
+        }
+    }
+}
+
+impl Display for CommandAnswerItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommandAnswerItem::FileDesc { size, name } => write!(f, "{} {}", size, name),
+            CommandAnswerItem::DirDesc(name) => write!(f, "dir {}", name),
+            CommandAnswerItem::Other(line) => write!(f, "{}", line),
+        }
+    }
+}
+
 DefStaticStr!(CmdPrompt,"$ ");
 
+/// A line of the transcript, along with its span to report errors found while replaying it.
+type Spanned<T> = (T, Context, Context);
+type Line<T> = Located<Natural<T>>;
+type CommandParser = Couple<Line<Command>, LineSep, Seq<Line<CommandAnswerItem>, LineSep, SkipFinal>, SplitFirst>;
+type Parser = Seq<CommandParser, StrSep<CmdPrompt>, Skip>;
+
+pub const SOLVER: Solver = Solver::new::<FileSystem>(7, "No Space Left On Device", [AnswerKind::Integer; 2]);
+
 impl Problem for FileSystem {
-    fn parse(lines: Vec<String>) -> Result<Self, ParsingError> {
-        type CommandParser =
-            Couple<Natural<Command>, LineSep, Seq<Natural<CommandAnswerItem>, LineSep, SkipFinal>, SplitFirst>;
-        let cmds = Seq::<CommandParser, StrSep<CmdPrompt>, Skip>::parse(lines.join("\n").as_bytes())?;
+    fn parse(lines: &[String]) -> Result<Self, ParsingError> {
+        Self::parse_with(lines, &Params::default())
+    }
+
+    fn parse_with(lines: &[String], params: &Params) -> Result<Self, ParsingError> {
+        let unknown_commands = params.parse_or("unknown-commands", UnknownCommands::default())?;
+        let cmds = Parser::parse_lines(lines)?;
 
-        let mut builder = FileSystemBuilder::new();
+        let mut builder = FileSystemBuilder::new(unknown_commands);
 
         for cmd in cmds {
-            builder
-                .interpret_command(cmd)
-                .map_err(|e| ParsingError::UnverifiedConstraint(e))?;
+            builder.interpret_command(cmd)?;
         }
-        Ok(Self(builder.root))
+        Ok(builder.build())
     }
 
-    fn part_one(&self) -> Result<String, SolvingError> {
-        Ok(self.0.total_sum_of_subdirectories(100000).to_string())
+    fn part_one(&self) -> Result<Answer, SolvingError> {
+        Ok(self.0.total_sum_of_subdirectories(100000).into())
     }
 
-    fn part_two(&self) -> Result<String, SolvingError> {
+    fn part_two(&self) -> Result<Answer, SolvingError> {
         let available = 70000000;
         let required = 30000000;
         let current = self.0.total_size();
@@ -238,8 +439,198 @@ impl Problem for FileSystem {
         let to_free = required - free;
         self.0
             .smallest_dir_with_size_over(to_free)
-            .as_ref()
-            .map(ToString::to_string)
+            .map(Answer::from)
             .ok_or(SolvingError::ExpectationUnfulfilled("No sol".into()))
     }
+
+    fn params() -> Vec<(&'static str, &'static str)> {
+        vec![("unknown-commands", "what to do with an unknown command, skip, warn or fail (by default)")]
+    }
+
+    fn warnings(&self) -> &[parse::Error] {
+        &self.1
+    }
+
+    fn extra_parts() -> Vec<ExtraPart<Self>> {
+        vec![
+            ExtraPart {
+                name: "tree",
+                description: "the browsed directory tree",
+                run: Self::tree,
+            },
+            ExtraPart {
+                name: "du",
+                description: "the total size of each directory",
+                run: Self::disk_usage,
+            },
+        ]
+    }
+}
+
+/// Seeded transcript browsing `size` directories holding 1 to 4 files each, sized so that the whole file system
+/// weighs about 50000000 and part two has to free some space, as in the real inputs. Besides `cd ..`, it sometimes
+/// climbs back with an absolute path and checks where it is with `pwd`.
+pub fn generate(seed: u64, size: usize) -> Vec<String> {
+    let size = size.max(1);
+    let mut rng = Rng::new(seed);
+    // The root is directory 0, and any other one lies in a directory created before it.
+    let mut children = vec![vec![]; size];
+    for dir in 1..size {
+        children[rng.range(0..=dir - 1)].push(dir);
+    }
+    let weights: Vec<Vec<usize>> = (0..size)
+        .map(|_| (0..rng.range(1..=4)).map(|_| rng.range(1..=1000)).collect())
+        .collect();
+    let total: usize = weights.iter().flatten().sum();
+    let files: Vec<Vec<usize>> = weights
+        .iter()
+        .map(|dir| dir.iter().map(|weight| weight * 50_000_000 / total).collect())
+        .collect();
+
+    struct Browser<'a> {
+        rng: Rng,
+        children: &'a [Vec<usize>],
+        files: &'a [Vec<usize>],
+        lines: Vec<String>,
+    }
+    impl Browser<'_> {
+        fn browse(&mut self, dir: usize, path: &str) {
+            if self.rng.range(0..=9) == 0 {
+                self.lines.extend(["$ pwd".to_string(), path.to_string()]);
+            }
+            self.lines.push("$ ls".to_string());
+            self.lines.extend(self.children[dir].iter().map(|child| format!("dir d{}", child)));
+            let files = self.files[dir].iter().enumerate();
+            self.lines.extend(files.map(|(i, size)| format!("{} f{}.txt", size, i)));
+            let children = self.children;
+            for &child in &children[dir] {
+                self.lines.push(format!("$ cd d{}", child));
+                self.browse(child, &join(path, &format!("d{}", child)));
+                match self.rng.range(0..=3) {
+                    0 => self.lines.push(format!("$ cd {}", path)),
+                    _ => self.lines.push("$ cd ..".to_string()),
+                }
+            }
+        }
+    }
+    let mut browser = Browser {
+        rng,
+        children: &children,
+        files: &files,
+        lines: vec!["$ cd /".to_string()],
+    };
+    browser.browse(0, "/");
+    let mut lines = browser.lines;
+    // Like the real ones, the transcript ends on a listing rather than on climbing back to the root.
+    while lines.last().is_some_and(|line| line.starts_with("$ cd ")) {
+        lines.pop();
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::parse::Format;
+
+    fn lines(input: &str) -> Vec<String> {
+        input.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn it_looks_up_paths_and_sizes() {
+        let file_system = FileSystem::parse(&lines(include_str!("../inputs/7.example"))).unwrap();
+        assert!(matches!(file_system.get("/a/e"), Some(Entry::Directory(dir)) if dir.total_size() == 584));
+        assert!(matches!(file_system.get("/d/k"), Some(Entry::File(file)) if file.size() == 7214296));
+        assert!(file_system.get("/a/k").is_none() && file_system.get("a").is_none());
+
+        let du = file_system.du();
+        assert_eq!(du.first(), Some(&("/a/e".to_string(), 584)));
+        assert_eq!(du.last(), Some(&("/".to_string(), 48381165)));
+    }
+
+    #[test]
+    fn it_rejects_moving_where_it_cannot_and_keeps_directories_listed_again() {
+        let err = FileSystem::parse(&lines("$ cd /\n$ ls\ndir a\n$ cd b\n$ ls\n1 x")).unwrap_err();
+        assert_eq!(err.to_string(), "4:3: Transcript: cd into 'b', which isn't listed in /");
+        let err = FileSystem::parse(&lines("$ cd ..\n$ ls\n1 x")).unwrap_err();
+        assert_eq!(err.to_string(), "1:3: Transcript: cd .. at the root, which has no parent");
+        let err = FileSystem::parse(&lines("$ ls\n1 x\nnot a file")).unwrap_err();
+        assert_eq!(err.to_string(), "3:1: Transcript: 'not a file' is neither a directory nor a file");
+
+        let file_system = FileSystem::parse(&lines("$ ls\ndir a\n$ cd a\n$ ls\n10 x\n$ cd /\n$ ls\ndir a\n5 y")).unwrap();
+        assert_eq!(file_system.0.total_size(), 15);
+    }
+
+    #[test]
+    fn it_follows_multi_segment_paths_and_checks_pwd() {
+        let transcript = "$ ls\ndir a\n$ cd a\n$ ls\ndir b\n$ cd b/\n$ ls\n1 x\n$ cd /a/./b/../b\n$ pwd\n/a/b\n\
+                          $ cd ../..\n$ pwd\n/\n$ cd a/b\n$ ls\n2 y";
+        let file_system = FileSystem::parse(&lines(transcript)).unwrap();
+        assert!(matches!(file_system.get("/a/b/y"), Some(Entry::File(file)) if file.size() == 2));
+
+        let err = FileSystem::parse(&lines("$ ls\ndir a\n$ cd a/c\n$ pwd\n/a")).unwrap_err();
+        assert_eq!(err.to_string(), "3:3: Transcript: cd into 'c', which isn't listed in /a");
+        let err = FileSystem::parse(&lines("$ ls\ndir a\n$ cd a\n$ pwd\n/b")).unwrap_err();
+        assert_eq!(err.to_string(), "4:3: Transcript: pwd shows '/b' in /a");
+    }
+
+    #[test]
+    fn it_skips_warns_or_fails_on_unknown_commands() {
+        let transcript = lines("$ ls\n1 x\n$ echo hi there\nhi there\n$ ls\n2 y");
+        let err = FileSystem::parse(&transcript).unwrap_err();
+        assert!(err.to_string().starts_with("3:3: Transcript: unknown command 'echo hi there'"));
+
+        let warning = "3:3: Transcript: unknown command 'echo hi there', skipped";
+        for (policy, warnings) in [("skip", vec![]), ("warn", vec![warning])] {
+            let params = [("unknown-commands".to_string(), policy.to_string())].into_iter().collect();
+            let file_system = FileSystem::parse_with(&transcript, &params).unwrap();
+            assert_eq!(file_system.0.total_size(), 2);
+            assert_eq!(file_system.warnings().iter().map(ToString::to_string).collect::<Vec<_>>(), warnings);
+        }
+        let params = [("unknown-commands".to_string(), "ignore".to_string())].into_iter().collect();
+        assert!(FileSystem::parse_with(&transcript, &params).is_err());
+    }
+
+    fn command() -> impl Strategy<Value = Command> {
+        prop_oneof![
+            Just(Command::Ls),
+            Just(Command::Pwd),
+            "/|\\.\\.|/?[a-z]{1,8}(/[a-z.]{1,8}){0,2}".prop_map(Command::Cd),
+            "echo [a-z]{1,8}".prop_map(Command::Other),
+        ]
+    }
+
+    fn answer_item() -> impl Strategy<Value = CommandAnswerItem> {
+        prop_oneof![
+            (any::<usize>(), "[a-z.]{1,8}").prop_map(|(size, name)| CommandAnswerItem::FileDesc { size, name }),
+            "[a-z]{1,8}".prop_map(CommandAnswerItem::DirDesc),
+            "/[a-z]{1,8}|[a-z]{1,8} [a-z]{1,8} [a-z]{1,8}".prop_map(CommandAnswerItem::Other),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn it_parses_back_its_formatted_input(
+            commands in prop::collection::vec((command(), prop::collection::vec(answer_item(), 0..5)), 1..10)
+        ) {
+            let at = Context::default();
+            let located: Vec<_> = commands
+                .iter()
+                .map(|(command, items)| {
+                    ((command.clone(), at, at), items.iter().map(|item| (item.clone(), at, at)).collect())
+                })
+                .collect();
+            let input = Parser::format(&located);
+            let parsed = Parser::parse(input.as_bytes()).map(|commands| {
+                commands
+                    .into_iter()
+                    .map(|((command, _, _), items)| (command, items.into_iter().map(|(item, _, _)| item).collect()))
+                    .collect::<Vec<(Command, Vec<CommandAnswerItem>)>>()
+            });
+            prop_assert_eq!(parsed, Ok(commands));
+        }
+    }
 }
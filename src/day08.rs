@@ -1,14 +1,18 @@
+use serde::Serialize;
+
 use crate::{
+    generate::Rng,
     parse::{
+        grid::Matrix,
         natural::Natural,
-        separator::{Empty, LineSep, StrSep},
-        seq::Seq,
+        separator::{Empty, StrSep},
+        Format,
     },
-    problem::{ParsingError, Problem, SolvingError},
+    problem::{Answer, AnswerKind, ParsingError, Problem, Solver, SolvingError},
 };
 use itertools::Itertools;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct Forest(Vec<Vec<usize>>);
 
 #[derive(PartialEq, Eq, Debug, Copy, Clone)]
@@ -157,38 +161,65 @@ enum Edge {
     Right,
 }
 
-use crate::parse::{table, ParseExt};
+use crate::parse::{grid::Grid, ParseExt};
+
+type Parser = Grid<1, StrSep<Empty>, Natural<usize>>;
+
+pub const SOLVER: Solver = Solver::new::<Forest>(8, "Treetop Tree House", [AnswerKind::Integer; 2]);
+
 impl Problem for Forest {
-    fn parse(lines: Vec<String>) -> Result<Self, ParsingError> {
-        type Parser = Seq<table::Table<1, StrSep<Empty>, Natural<usize>>, LineSep>;
-        let forest = Parser::parse(lines.join("\n").as_bytes())?;
+    fn parse(lines: &[String]) -> Result<Self, ParsingError> {
+        let forest = Parser::parse_lines(lines)?.into_rows();
 
-        if forest.len() == 0 {
+        if forest.is_empty() {
             return Err(ParsingError::UnverifiedConstraint("The forest is empty".into()));
         }
-        if forest[0].len() == 0 {
+        if forest[0].is_empty() {
             return Err(ParsingError::UnverifiedConstraint("The forest is empty".into()));
         }
-
-        if !forest.iter().map(Vec::len).all_equal() {
-            return Err(ParsingError::UnverifiedConstraint("The forest is not a square".into()));
-        }
         Ok(Self(forest))
     }
 
-    fn part_one(&self) -> Result<String, SolvingError> {
-        let visible_trees_count = (0..self.0.get(0).unwrap().len())
+    fn part_one(&self) -> Result<Answer, SolvingError> {
+        let visible_trees_count = (0..self.0[0].len())
             .cartesian_product(0..self.0.len())
             .filter(|tree_coord| self.is_visible_tree((*tree_coord).into()))
             .count();
-        Ok(visible_trees_count.to_string())
+        Ok(visible_trees_count.into())
     }
 
-    fn part_two(&self) -> Result<String, SolvingError> {
-        let max_scenir_score = (0..self.0.get(0).unwrap().len())
+    fn part_two(&self) -> Result<Answer, SolvingError> {
+        let max_scenir_score = (0..self.0[0].len())
             .cartesian_product(0..self.0.len())
             .max_by_key(|tree_coord| self.scenic_score((*tree_coord).into()))
             .map(|tree_coord| self.scenic_score((tree_coord).into()));
-        Ok(max_scenir_score.unwrap().to_string())
+        Ok(max_scenir_score.unwrap().into())
+    }
+}
+
+/// Seeded forest of `size` by `size` trees.
+pub fn generate(seed: u64, size: usize) -> Vec<String> {
+    let mut rng = Rng::new(seed);
+    let rows = (0..size).map(|_| (0..size).map(|_| rng.range(0..=9)).collect()).collect();
+    Parser::format(&Matrix::from_rows(rows).unwrap()).lines().map(String::from).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn it_parses_back_its_formatted_input(
+            forest in (1..20usize).prop_flat_map(|width| {
+                prop::collection::vec(prop::collection::vec(0..=9usize, width), 1..20)
+            })
+        ) {
+            let forest = Matrix::from_rows(forest).unwrap();
+            let input = Parser::format(&forest);
+            prop_assert_eq!(Parser::parse(input.as_bytes()), Ok(forest));
+        }
     }
 }
@@ -1,30 +1,42 @@
+use std::collections::HashSet;
+
 use derive_more::{Add, From, Sub};
 use itertools::Itertools;
-use strum_macros::EnumString;
+use serde::Serialize;
+use strum_macros::{Display, EnumString};
 
+use crate::generate::Rng;
 use crate::prelude::*;
 
-#[derive(EnumString, Debug, Copy, Clone)]
+#[derive(EnumString, Display, Debug, Copy, Clone, PartialEq, Eq, Serialize)]
 enum Direction {
     U,
     D,
     R,
     L,
+    UR,
+    UL,
+    DR,
+    DL,
 }
 
-impl Into<Coord> for Direction {
-    fn into(self) -> Coord {
-        match self {
+impl From<Direction> for Coord {
+    fn from(direction: Direction) -> Self {
+        match direction {
             Direction::U => (0, 1),
             Direction::D => (0, -1),
             Direction::R => (1, 0),
             Direction::L => (-1, 0),
+            Direction::UR => (1, 1),
+            Direction::UL => (-1, 1),
+            Direction::DR => (1, -1),
+            Direction::DL => (-1, -1),
         }
         .into()
     }
 }
 
-#[derive(From, Debug, Clone, Copy)]
+#[derive(From, Debug, Clone, Copy, Serialize)]
 pub struct Movement(Direction, usize);
 
 impl IntoIterator for Movement {
@@ -37,8 +49,17 @@ impl IntoIterator for Movement {
     }
 }
 
-#[derive(Debug)]
-pub struct Movements(Vec<Movement>);
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
+pub struct Movements {
+    movements: Vec<Movement>,
+    /// Knots of the rope of the extra parts, set with `--param knots=<n>`.
+    #[serde(skip)]
+    knots: usize,
+    /// Whether the trail also shows where each knot ends, set with `--param show-knots=true`.
+    #[serde(skip)]
+    show_knots: bool,
+}
 
 #[derive(Add, Sub, From, Debug, Copy, Clone, Hash, PartialEq, Eq)]
 pub struct Coord {
@@ -47,11 +68,7 @@ pub struct Coord {
 }
 
 fn maxxed(x: isize) -> isize {
-    match x {
-        x if x <= -1 => -1,
-        x if x >= 1 => 1,
-        x => x,
-    }
+    x.clamp(-1, 1)
 }
 
 impl Coord {
@@ -71,7 +88,18 @@ struct Rope {
 }
 
 impl Rope {
-    fn move_toward(mut self, mov_coord: Coord) -> Self {
+    /// Rope of `knots` knots, at least one, all at the start.
+    fn new(knots: usize) -> Self {
+        Self {
+            knots: vec![(0, 0).into(); knots],
+        }
+    }
+
+    fn tail(&self) -> Coord {
+        *self.knots.last().unwrap()
+    }
+
+    fn move_toward(&mut self, mov_coord: Coord) {
         self.knots[0] = self.knots[0] + mov_coord;
         for i in 1..self.knots.len() {
             let heading_knot = self.knots[i - 1];
@@ -81,48 +109,250 @@ impl Rope {
                 *curr = *curr + delta
             }
         }
+    }
+}
+
+/// Positions visited by the tail, the start and the final knots of a rope, within the bounds `min..=max`.
+struct Trail {
+    visited: Vec<Coord>,
+    knots: Vec<Coord>,
+    min: Coord,
+    max: Coord,
+}
+
+impl Trail {
+    const START: Coord = Coord { x: 0, y: 0 };
+
+    fn new(visited: Vec<Coord>, knots: Vec<Coord>) -> Self {
+        let all = || visited.iter().chain(&knots).chain([&Self::START]);
+        let (min, max) = (
+            (all().map(|c| c.x).min().unwrap(), all().map(|c| c.y).min().unwrap()).into(),
+            (all().map(|c| c.x).max().unwrap(), all().map(|c| c.y).max().unwrap()).into(),
+        );
+        Self {
+            visited,
+            knots,
+            min,
+            max,
+        }
+    }
+
+    /// As drawn in the puzzle, from the top row: `H` for the head, the index of the other knots (`+` past 9), `s` for
+    /// the start and `#` for the visited positions.
+    fn rows(&self) -> Vec<String> {
+        let visited: HashSet<&Coord> = self.visited.iter().collect();
+        (self.min.y..=self.max.y)
+            .rev()
+            .map(|y| {
+                (self.min.x..=self.max.x)
+                    .map(|x| {
+                        let coord = Coord { x, y };
+                        match self.knots.iter().position(|knot| knot == &coord) {
+                            Some(0) => 'H',
+                            Some(index) => char::from_digit(index as u32, 10).unwrap_or('+'),
+                            None if coord == Self::START => 's',
+                            None if visited.contains(&coord) => '#',
+                            None => '.',
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
 
-        self
+    /// As an SVG image, each visited position being a square of side 1, the start a green one and the knots red dots.
+    fn svg(&self) -> String {
+        let (width, height) = (self.max.x - self.min.x + 1, self.max.y - self.min.y + 1);
+        // The SVG y axis goes down.
+        let at = |coord: &Coord| (coord.x - self.min.x, self.max.y - coord.y);
+        let squares = self.visited.iter().filter(|coord| **coord != Self::START).map(|coord| {
+            let (x, y) = at(coord);
+            format!("  <rect x=\"{}\" y=\"{}\" width=\"1\" height=\"1\"/>", x, y)
+        });
+        let (x, y) = at(&Self::START);
+        let start = format!("  <rect x=\"{}\" y=\"{}\" width=\"1\" height=\"1\" fill=\"green\"/>", x, y);
+        let knots = self.knots.iter().map(|coord| {
+            let (x, y) = at(coord);
+            format!("  <circle cx=\"{}.5\" cy=\"{}.5\" r=\"0.4\" fill=\"red\"/>", x, y)
+        });
+        std::iter::once(format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {} {}\" width=\"{}\" height=\"{}\">",
+            width,
+            height,
+            width * 10,
+            height * 10
+        ))
+        .chain(squares)
+        .chain(std::iter::once(start))
+        .chain(knots)
+        .chain(std::iter::once("</svg>".to_string()))
+        .join("\n")
     }
 }
 
+type Parser = SeqRecover<Couple<Natural<Direction>, SpaceSep, Natural<usize>>, LineSep>;
+
+pub const SOLVER: Solver = Solver::new::<Movements>(9, "Rope Bridge", [AnswerKind::Integer; 2]);
+
 impl Problem for Movements {
-    fn parse(lines: Vec<String>) -> Result<Self, ParsingError> {
-        type Parser = Seq<Couple<Natural<Direction>, SpaceSep, Natural<usize>>, LineSep>;
-        let movs = Parser::parse(lines.join("\n").as_bytes())?
+    fn parse(lines: &[String]) -> Result<Self, ParsingError> {
+        Self::parse_with(lines, &Params::default())
+    }
+
+    fn parse_with(lines: &[String], params: &Params) -> Result<Self, ParsingError> {
+        let knots = params.parse_or("knots", 10)?;
+        if knots == 0 {
+            return Err(ParsingError::UnverifiedConstraint("A rope has at least one knot".into()));
+        }
+        let show_knots = params.parse_or("show-knots", false)?;
+        let movs = ParsingError::recovered(Parser::parse_lines(lines)?)?
             .into_iter()
             .map(Movement::from)
             .collect();
-        Ok(Self(movs))
+        Ok(Self {
+            movements: movs,
+            knots,
+            show_knots,
+        })
     }
 
-    fn part_one(&self) -> Result<String, SolvingError> {
-        let mut rope = Rope {
-            knots: std::iter::repeat_n((0, 0).into(), 2).collect(),
-        };
-        let rope_states = self.0.clone().into_iter().flat_map(IntoIterator::into_iter).map(|mov| {
-            rope = rope.clone().move_toward(mov);
-            rope.clone()
+    fn part_one(&self) -> Result<Answer, SolvingError> {
+        Ok(self.pull(2).0.len().into())
+    }
+
+    fn part_two(&self) -> Result<Answer, SolvingError> {
+        Ok(self.pull(10).0.len().into())
+    }
+
+    fn params() -> Vec<(&'static str, &'static str)> {
+        vec![
+            ("knots", "number of knots of the rope pulled by the extra parts, 10 by default"),
+            ("show-knots", "whether the trails show where each knot ends, false by default"),
+        ]
+    }
+
+    fn extra_parts() -> Vec<ExtraPart<Self>> {
+        vec![
+            ExtraPart {
+                name: "visited",
+                description: "the positions visited by the tail of a rope of --param knots=<n> knots, 10 by default",
+                run: Self::visited,
+            },
+            ExtraPart {
+                name: "trail",
+                description: "the positions visited by the tail, and the knots with --param show-knots=true",
+                run: Self::trail_ascii,
+            },
+            ExtraPart {
+                name: "trail-svg",
+                description: "the trail as an SVG image",
+                run: Self::trail_svg,
+            },
+        ]
+    }
+}
+
+impl Movements {
+    /// Positions visited by the tail of a rope of `knots` knots, from the start and in the order of their first visit,
+    /// along with the rope once every movement is done.
+    fn pull(&self, knots: usize) -> (Vec<Coord>, Rope) {
+        let mut rope = Rope::new(knots);
+        let steps = self.movements.iter().copied().flat_map(IntoIterator::into_iter).map(|mov| {
+            rope.move_toward(mov);
+            rope.tail()
         });
-        let res = rope_states
-            .map(|rope| rope.knots.last().unwrap().clone())
-            .unique()
-            .count();
-        Ok(res.to_string())
+        let visited = std::iter::once(Trail::START).chain(steps).unique().collect();
+        (visited, rope)
+    }
+
+    fn trail(&self) -> Trail {
+        let (visited, rope) = self.pull(self.knots);
+        Trail::new(visited, if self.show_knots { rope.knots } else { vec![] })
+    }
+
+    fn visited(&self) -> Result<Answer, SolvingError> {
+        Ok(self.pull(self.knots).0.len().into())
+    }
+
+    fn trail_ascii(&self) -> Result<Answer, SolvingError> {
+        Ok(Answer::Grid(self.trail().rows()))
+    }
+
+    fn trail_svg(&self) -> Result<Answer, SolvingError> {
+        Ok(Answer::Text(self.trail().svg()))
+    }
+}
+
+/// Seeded input of `size` movements of 1 to 20 steps.
+pub fn generate(seed: u64, size: usize) -> Vec<String> {
+    let mut rng = Rng::new(seed);
+    let directions = [Direction::U, Direction::D, Direction::R, Direction::L];
+    let movements = (0..size).map(|_| (*rng.choose(&directions), rng.range(1..=20))).collect();
+    Parser::format(&(movements, vec![])).lines().map(String::from).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn params(params: &[(&str, &str)]) -> Params {
+        params.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    fn example(with: &[(&str, &str)]) -> Movements {
+        let lines: Vec<String> = include_str!("../inputs/9.example").lines().map(String::from).collect();
+        Movements::parse_with(&lines, &params(with)).unwrap()
+    }
+
+    #[test]
+    fn it_pulls_ropes_of_any_length_in_any_direction() {
+        assert_eq!(example(&[("knots", "2")]).visited().unwrap(), Answer::Integer(13));
+        assert_eq!(example(&[]).visited().unwrap(), example(&[]).part_two().unwrap());
+
+        let diagonal = Movements::parse(&["UR 2".to_string(), "DL 1".to_string()]).unwrap();
+        assert_eq!(diagonal.pull(2).0, vec![(0, 0).into(), (1, 1).into()]);
+        assert!(Movements::parse_with(&["U 1".to_string()], &params(&[("knots", "0")])).is_err());
+
+        // A single knot leaves the start at once, which it still visited.
+        let straight = Movements::parse_with(&["R 4".to_string()], &params(&[("knots", "1")])).unwrap();
+        assert_eq!(straight.visited().unwrap(), Answer::Integer(5));
+        assert_eq!(straight.trail_ascii().unwrap(), Answer::Grid(vec!["s####".to_string()]));
     }
 
-    fn part_two(&self) -> Result<String, SolvingError> {
-        let mut rope = Rope {
-            knots: std::iter::repeat_n((0, 0).into(), 10).collect(),
+    #[test]
+    fn it_draws_the_trail_as_in_the_puzzle() {
+        let trail = example(&[("knots", "2")]).trail_ascii().unwrap();
+        assert_eq!(trail, Answer::Grid(["..##.", "...##", ".####", "....#", "s###."].map(String::from).to_vec()));
+        let trail = example(&[("knots", "2"), ("show-knots", "true")]).trail_ascii().unwrap();
+        assert_eq!(trail, Answer::Grid(["..##.", "...##", ".1H##", "....#", "s###."].map(String::from).to_vec()));
+
+        let Answer::Text(svg) = example(&[("knots", "2"), ("show-knots", "true")]).trail_svg().unwrap() else {
+            panic!("an SVG image is text")
         };
-        let rope_states = self.0.clone().into_iter().flat_map(IntoIterator::into_iter).map(|mov| {
-            rope = rope.clone().move_toward(mov);
-            rope.clone()
-        });
-        let res = rope_states
-            .map(|rope| rope.knots.last().unwrap().clone())
-            .unique()
-            .count();
-        Ok(res.to_string())
+        assert!(svg.starts_with("<svg") && svg.ends_with("</svg>"));
+        assert_eq!((svg.matches("<rect").count(), svg.matches("<circle").count()), (13, 2));
+    }
+
+    fn direction() -> impl Strategy<Value = Direction> {
+        prop_oneof![
+            Just(Direction::U),
+            Just(Direction::D),
+            Just(Direction::R),
+            Just(Direction::L),
+            Just(Direction::UR),
+            Just(Direction::UL),
+            Just(Direction::DR),
+            Just(Direction::DL),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn it_parses_back_its_formatted_input(movements in prop::collection::vec((direction(), any::<usize>()), 1..20)) {
+            let input = Parser::format(&(movements.clone(), vec![]));
+            prop_assert_eq!(Parser::parse(input.as_bytes()), Ok((movements, vec![])));
+        }
     }
 }
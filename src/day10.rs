@@ -1,27 +1,21 @@
 use crate::prelude::*;
 use itertools::Itertools;
-use strum_macros::EnumString;
+use serde::Serialize;
+use strum_macros::{Display, EnumString};
 
-#[derive(EnumString, Debug, Copy, Clone)]
-#[strum(serialize_all = "lowercase")]
-pub enum CodeOp {
-    NoOp,
-    AddX,
-}
-
-#[derive(EnumString, Debug, Copy, Clone)]
+#[derive(EnumString, Display, Debug, Copy, Clone, PartialEq, Eq, Serialize)]
 #[strum(serialize_all = "lowercase")]
 pub enum NoArgCodeOp {
     NoOp,
 }
 
-#[derive(EnumString, Debug, Copy, Clone)]
+#[derive(EnumString, Display, Debug, Copy, Clone, PartialEq, Eq, Serialize)]
 #[strum(serialize_all = "lowercase")]
 pub enum OneArgCodeOp {
     AddX,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Serialize)]
 pub enum Instruction {
     NoArgCodeOp(NoArgCodeOp),
     OneArgCodeOp(OneArgCodeOp, isize),
@@ -31,7 +25,9 @@ impl Instruction {
     fn into_event_vec(self) -> Vec<ProcEvent> {
         match self {
             Instruction::NoArgCodeOp(NoArgCodeOp::NoOp) => vec![ProcEvent::NothingHappens],
-            Instruction::OneArgCodeOp(OneArgCodeOp::AddX, op) => vec![ProcEvent::NothingHappens, ProcEvent::AddX(op)],
+            Instruction::OneArgCodeOp(OneArgCodeOp::AddX, op) => {
+                vec![ProcEvent::NothingHappens, ProcEvent::AddX(op)]
+            }
         }
     }
 }
@@ -50,49 +46,53 @@ impl From<either::Either<NoArgCodeOp, (OneArgCodeOp, isize)>> for Instruction {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct Program(Vec<Instruction>);
 
+type Parser =
+    SeqRecover<Either<Natural<NoArgCodeOp>, Couple<Natural<OneArgCodeOp>, SpaceSep, Natural<isize>>>, LineSep>;
+
+pub const SOLVER: Solver = Solver::new::<Program>(10, "Cathode-Ray Tube", [AnswerKind::Integer, AnswerKind::Grid]);
+
 impl Problem for Program {
-    fn parse(lines: Vec<String>) -> Result<Self, ParsingError> {
-        type Parser =
-            Seq<Either<Natural<NoArgCodeOp>, Couple<Natural<OneArgCodeOp>, SpaceSep, Natural<isize>>>, LineSep>;
-        let pb = Parser::parse(lines.join("\n").as_bytes())?
+    fn parse(lines: &[String]) -> Result<Self, ParsingError> {
+        let pb = ParsingError::recovered(Parser::parse_lines(lines)?)?
             .into_iter()
             .map(Instruction::from)
             .collect();
         Ok(Self(pb))
     }
 
-    fn part_one(&self) -> Result<String, SolvingError> {
-        let mut x: isize = 1;
+    fn part_one(&self) -> Result<Answer, SolvingError> {
         let res = self
-            .0
-            .iter()
-            .flat_map(|ins| Instruction::into_event_vec(*ins).into_iter())
-            .map(|ev| {
-                let x_start = x;
-                match ev {
-                    ProcEvent::NothingHappens => (),
-                    ProcEvent::AddX(op) => x += op,
-                }
-                x_start
-            })
-            .enumerate()
-            .map(|(zeroed_cycle, v)| (zeroed_cycle + 1, v))
-            .filter(|(cycle, _)| ((*cycle + 20) % 40 == 0 && *cycle <= 220))
+            .x_during_cycles()
+            .filter(|(cycle, _)| (*cycle + 20) % 40 == 0 && *cycle <= 220)
             .map(|(cycle, value)| cycle as isize * value)
             .sum::<isize>();
-        Ok(res.to_string())
+        Ok(res.into())
     }
 
-    fn part_two(&self) -> Result<String, SolvingError> {
-        let mut x = 1;
-        let res = self
-            .0
+    fn part_two(&self) -> Result<Answer, SolvingError> {
+        Ok(Answer::Grid(self.screen()))
+    }
+
+    fn extra_parts() -> Vec<ExtraPart<Self>> {
+        vec![ExtraPart {
+            name: "render-svg",
+            description: "the CRT of part two as an SVG image",
+            run: Self::render_svg,
+        }]
+    }
+}
+
+impl Program {
+    /// Value of the X register during each cycle, numbered from 1.
+    fn x_during_cycles(&self) -> impl Iterator<Item = (usize, isize)> + '_ {
+        let mut x: isize = 1;
+        self.0
             .iter()
             .flat_map(|ins| Instruction::into_event_vec(*ins).into_iter())
-            .map(|ev| {
+            .map(move |ev| {
                 let x_start = x;
                 match ev {
                     ProcEvent::NothingHappens => (),
@@ -102,6 +102,12 @@ impl Problem for Program {
             })
             .enumerate()
             .map(|(zeroed_cycle, v)| (zeroed_cycle + 1, v))
+    }
+
+    /// Rows of the CRT once the whole program ran.
+    fn screen(&self) -> Vec<String> {
+        let res = self
+            .x_during_cycles()
             .map(|(cycle, sprite_x)| {
                 let x = (cycle - 1) % 40;
                 if isize::abs(sprite_x - (x as isize)) <= 1 {
@@ -111,11 +117,48 @@ impl Problem for Program {
                 }
             })
             .collect::<Vec<_>>();
-        let lines = res
-            .iter()
-            .array_chunks()
-            .map(|v: [_; 40]| Vec::from(v).into_iter().join(""))
-            .join("\n");
-        Ok(lines)
+        res.chunks_exact(40).map(|row| row.iter().join("")).collect()
+    }
+
+    /// The CRT as an SVG image, a lit pixel being a square of side 1.
+    fn render_svg(&self) -> Result<Answer, SolvingError> {
+        let screen = self.screen();
+        let pixels = screen.iter().enumerate().flat_map(|(y, row)| {
+            row.chars()
+                .enumerate()
+                .filter(|(_, pixel)| *pixel == '#')
+                .map(move |(x, _)| format!("  <rect x=\"{}\" y=\"{}\" width=\"1\" height=\"1\"/>", x, y))
+        });
+        let svg = std::iter::once(format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 40 {}\" width=\"400\" height=\"{}\">",
+            screen.len(),
+            screen.len() * 10
+        ))
+        .chain(pixels)
+        .chain(std::iter::once("</svg>".to_string()))
+        .join("\n");
+        Ok(Answer::Text(svg))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn instruction() -> impl Strategy<Value = either::Either<NoArgCodeOp, (OneArgCodeOp, isize)>> {
+        prop_oneof![
+            Just(either::Left(NoArgCodeOp::NoOp)),
+            any::<isize>().prop_map(|arg| either::Right((OneArgCodeOp::AddX, arg))),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn it_parses_back_its_formatted_input(instructions in prop::collection::vec(instruction(), 1..20)) {
+            let input = Parser::format(&(instructions.clone(), vec![]));
+            prop_assert_eq!(Parser::parse(input.as_bytes()), Ok((instructions, vec![])));
+        }
     }
 }
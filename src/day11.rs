@@ -1,10 +1,16 @@
-use crate::parse::StaticStr;
+use crate::parse::Parse;
 use itertools::Itertools;
-use strum_macros::EnumString;
+use serde::Serialize;
+use std::{collections::VecDeque, num::ParseIntError, str::FromStr};
+use strum_macros::{Display, EnumString};
 
-use crate::{parse::{separator::CommaSpace, Context, DefStaticStr}, prelude::*};
+use crate::{
+    generate::Rng,
+    parse::{separator::CommaSpace, DefStaticStr},
+    prelude::*,
+};
 
-#[derive(Debug, EnumString, Copy, Clone)]
+#[derive(Debug, EnumString, Display, Copy, Clone, PartialEq, Eq, Serialize)]
 enum Operator {
     #[strum(serialize = "+")]
     Plus,
@@ -12,7 +18,7 @@ enum Operator {
     Times,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
 enum Operande {
     Old,
     Raw(usize),
@@ -27,9 +33,24 @@ impl From<either::Either<[usize; 0], usize>> for Operande {
     }
 }
 
-#[derive(Debug, derive_more::From, Copy, Clone)]
+impl From<Operande> for either::Either<[usize; 0], usize> {
+    fn from(value: Operande) -> Self {
+        match value {
+            Operande::Old => either::Either::Left([]),
+            Operande::Raw(value) => either::Either::Right(value),
+        }
+    }
+}
+
+DefStaticStr!(Old, "old");
+// A bit of a hack here to check 'old' token
+type OperandeParser = Either<Capture<Old, 0, Natural<usize>>, Natural<usize>>;
+
+#[derive(Debug, Parse, Copy, Clone, PartialEq, Eq, Serialize)]
+#[parse(pattern = "{operator} {right}", format)]
 struct Operation {
     operator: Operator,
+    #[parse(with = "OperandeParser")]
     right: Operande,
 }
 
@@ -61,20 +82,26 @@ impl Operation {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Parse, Clone, PartialEq, Eq, Serialize)]
+#[parse(
+    pattern = "divisible by {divider}
+    If true: throw to monkey {monkey_if_true}
+    If false: throw to monkey {monkey_if_false}",
+    format
+)]
 struct ThrowFetch {
     divider: usize,
     monkey_if_true: usize,
     monkey_if_false: usize,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 struct Remind {
     divider: usize,
     value: usize,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 enum Item {
     Pure(usize),
     Reminds(Vec<Remind>),
@@ -95,11 +122,46 @@ impl From<usize> for Item {
     }
 }
 
-#[derive(Debug, Clone)]
+/// Only pure items are written as they are parsed, reminds being shown as `value mod divider`.
+impl std::fmt::Display for Item {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Item::Pure(value) => write!(f, "{}", value),
+            Item::Reminds(reminds) => write!(
+                f,
+                "{}",
+                reminds
+                    .iter()
+                    .map(|remind| format!("{} mod {}", remind.value, remind.divider))
+                    .join(" ")
+            ),
+        }
+    }
+}
+
+impl FromStr for Item {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        usize::from_str(s).map(Item::Pure)
+    }
+}
+
+#[derive(Debug, Parse, Clone, PartialEq, Eq, Serialize)]
+#[parse(
+    pattern = "Monkey {id}:
+  Starting items: {items}
+  Operation: new = old {operation}
+  Test: {throw_fetch}",
+    format
+)]
 struct Monkey {
     id: usize,
-    items: std::collections::VecDeque<Item>,
+    #[parse(with = "Seq<Natural<Item>, StrSep<CommaSpace>>")]
+    items: VecDeque<Item>,
+    #[parse(with = "OperationParser")]
     operation: Operation,
+    #[parse(with = "ThrowFetchParser")]
     throw_fetch: ThrowFetch,
 }
 
@@ -107,8 +169,8 @@ enum Error {
     WorryLevelTooHigh,
 }
 
-impl Into<SolvingError> for Error {
-    fn into(self) -> SolvingError {
+impl From<Error> for SolvingError {
+    fn from(_: Error) -> Self {
         SolvingError::ExpectationUnfulfilled("Worry level too high".to_string())
     }
 }
@@ -119,7 +181,6 @@ impl Monkey {
         self.items
             .pop_front()
             .map(|item| {
-                println!("Monkey {} inspect item {:?}", self.id, item);
                 let mut new_worry = self.operation.operate(item).ok_or(Error::WorryLevelTooHigh)?;
                 if divider != 1 {
                     if let Item::Pure(value) = new_worry {
@@ -136,78 +197,60 @@ impl Monkey {
     }
 }
 
-impl Monkey {
-    fn parse_from_capture(captured: [(Vec<u8>, Context, Context); 6]) -> Result<Self, ParsingError> {
-        let id = Natural::<usize>::parse_with_context(&captured[0].0, captured[0].1)?;
-        let items = Seq::<Natural<usize>, StrSep<CommaSpace>>::parse_with_context(&captured[1].0, captured[1].1)?;
-        DefStaticStr!(Old,"old");
-
-        // A bit of a hack here to check 'old' token
-        type OperandeParser = Either<Capture<Old, 0, Natural<usize>>, Natural<usize>>;
-        type OperationParser = Couple<Natural<Operator>, SpaceSep, OperandeParser>;
-        let operation = OperationParser::parse_with_context(&captured[2].0, captured[2].1)?;
-        let operation = (operation.0, Operande::from(operation.1)).into();
-
-        let divider = Natural::<usize>::parse_with_context(&captured[3].0, captured[3].1)?;
-        let monkey_if_true = Natural::<usize>::parse_with_context(&captured[4].0, captured[4].1)?;
-        let monkey_if_false = Natural::<usize>::parse_with_context(&captured[5].0, captured[5].1)?;
-        let throw_fetch = ThrowFetch {
-            divider,
-            monkey_if_true,
-            monkey_if_false,
-        };
-
-        Ok(Self {
-            id,
-            items: items.into_iter().map(|value| Item::Pure(value)).collect(),
-            operation,
-            throw_fetch,
-        })
-    }
-}
-
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct MonkeyBehaviors(Vec<Monkey>);
 
-DefStaticStr!(MonkeyFmt, "Monkey %:
-  Starting items: %
-  Operation: new = old %
-  Test: divisible by %
-    If true: throw to monkey %
-    If false: throw to monkey %"
-    );
-impl Problem for MonkeyBehaviors {
-    fn parse(lines: Vec<String>) -> Result<Self, ParsingError> {
-        
-        type MonkeyParser = Capture<MonkeyFmt, 6, crate::parse::keep::Keep>;
-        type NoteParser = Seq<MonkeyParser, EmptyLineSep>;
-        let monkey_behaviors = NoteParser::parse(lines.join("\n").as_bytes())?;
-        let monkey_behaviors = monkey_behaviors
-            .into_iter()
-            .map(Monkey::parse_from_capture)
-            .collect::<Result<_, ParsingError>>()?;
-
-        Ok(Self(monkey_behaviors))
-    }
-
-    fn part_one(&self) -> Result<String, SolvingError> {
+impl MonkeyBehaviors {
+    /// Plays the 20 rounds of part one and counts the items inspected by each monkey, logging every throw and the
+    /// items held after each round in `trace` when given.
+    fn play_part_one(&self, mut trace: Option<&mut Vec<String>>) -> Result<Vec<usize>, Error> {
         let mut state = self.clone();
 
         let mut inspections: Vec<_> = self.0.iter().map(|_| 0).collect();
-        for _ in 0..20 {
-            for monkey in 0..self.0.len() {
-                while let Some(throw) = state.0[monkey].inspect_item(3, 0).map_err(Into::into)? {
-                    inspections[monkey] += 1;
-                    println!("monkey {} thow {:?} at {}", monkey, throw.0, throw.1);
+        for round in 1..=20 {
+            for (monkey, inspected) in inspections.iter_mut().enumerate() {
+                while let Some(throw) = state.0[monkey].inspect_item(3, 0)? {
+                    *inspected += 1;
+                    if let Some(trace) = trace.as_mut() {
+                        trace.push(format!("Monkey {} throws {} to monkey {}", monkey, throw.0, throw.1));
+                    }
                     state.0[throw.1].items.push_back(throw.0);
                 }
             }
+            if let Some(trace) = trace.as_mut() {
+                trace.push(format!("After round {}:", round));
+                for monkey in &state.0 {
+                    trace.push(format!("Monkey {}: {}", monkey.id, monkey.items.iter().join(", ")));
+                }
+            }
         }
+        Ok(inspections)
+    }
+
+    fn trace(&self) -> Result<Answer, SolvingError> {
+        let mut trace = vec![];
+        self.play_part_one(Some(&mut trace))?;
+        Ok(Answer::Text(trace.join("\n")))
+    }
+}
+
+type Parser = Seq<MonkeyParser, EmptyLineSep>;
+
+pub const SOLVER: Solver = Solver::new::<MonkeyBehaviors>(11, "Monkey in the Middle", [AnswerKind::Integer; 2]);
+
+impl Problem for MonkeyBehaviors {
+    fn parse(lines: &[String]) -> Result<Self, ParsingError> {
+        let monkey_behaviors = Parser::parse_lines(lines)?;
+        Ok(Self(monkey_behaviors))
+    }
+
+    fn part_one(&self) -> Result<Answer, SolvingError> {
+        let inspections = self.play_part_one(None)?;
         let two_bests = inspections.into_iter().sorted().rev().take(2).collect::<Vec<_>>();
-        Ok((two_bests[0] * two_bests[1]).to_string())
+        Ok((two_bests[0] * two_bests[1]).into())
     }
 
-    fn part_two(&self) -> Result<String, SolvingError> {
+    fn part_two(&self) -> Result<Answer, SolvingError> {
         let divider: Vec<_> = self.0.iter().map(|monkey| monkey.throw_fetch.divider).collect();
         let mut monkeys = self.0.clone();
         for monkey in monkeys.iter_mut() {
@@ -233,15 +276,95 @@ impl Problem for MonkeyBehaviors {
         let mut inspections: Vec<_> = self.0.iter().map(|_| 0).collect();
         for _ in 0..10000 {
             for monkey in 0..self.0.len() {
-                while let Some(throw) = monkeys[monkey].inspect_item(1, 0).map_err(Into::into)? {
+                while let Some(throw) = monkeys[monkey].inspect_item(1, 0)? {
                     inspections[monkey] += 1;
-                    println!("monkey {} thow {:?} at {}", monkey, throw.0, throw.1);
                     monkeys[throw.1].items.push_back(throw.0);
                 }
             }
         }
-        println!("{:?}", inspections);
         let two_bests = inspections.into_iter().sorted().rev().take(2).collect::<Vec<_>>();
-        Ok((two_bests[0] as u64 * two_bests[1] as u64).to_string())
+        Ok((two_bests[0] as u64 * two_bests[1] as u64).into())
+    }
+
+    fn extra_parts() -> Vec<ExtraPart<Self>> {
+        vec![ExtraPart {
+            name: "trace",
+            description: "the throws and held items of each round of part one",
+            run: Self::trace,
+        }]
+    }
+}
+
+/// Seeded notes on `size` monkeys testing divisibility by distinct primes, as in the real inputs. A monkey only throws
+/// to the ones before it, the first one to any other, so that an item is inspected at most twice a round and worry
+/// levels of part one stay far from overflowing.
+pub fn generate(seed: u64, size: usize) -> Vec<String> {
+    let size = size.max(2);
+    let mut rng = Rng::new(seed);
+    let primes: Vec<usize> = (2..)
+        .filter(|n: &usize| (2..).take_while(|d| d * d <= *n).all(|d| n % d != 0))
+        .take(size)
+        .collect();
+    let target = |rng: &mut Rng, id: usize| match id {
+        0 => rng.range(1..=size - 1),
+        _ => rng.range(0..=id - 1),
+    };
+    let monkeys: Vec<Monkey> = (0..size)
+        .map(|id| Monkey {
+            id,
+            items: (0..rng.range(1..=5)).map(|_| Item::Pure(rng.range(50..=99))).collect(),
+            operation: Operation {
+                operator: *rng.choose(&[Operator::Plus, Operator::Times]),
+                right: Operande::Raw(rng.range(1..=5)),
+            },
+            throw_fetch: ThrowFetch {
+                divider: primes[id],
+                monkey_if_true: target(&mut rng, id),
+                monkey_if_false: target(&mut rng, id),
+            },
+        })
+        .collect();
+    Parser::format(&monkeys).lines().map(String::from).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn operation() -> impl Strategy<Value = Operation> {
+        let operator = prop_oneof![Just(Operator::Plus), Just(Operator::Times)];
+        let right = prop_oneof![Just(Operande::Old), any::<usize>().prop_map(Operande::Raw)];
+        (operator, right).prop_map(|(operator, right)| Operation { operator, right })
+    }
+
+    fn monkey() -> impl Strategy<Value = Monkey> {
+        (
+            any::<usize>(),
+            prop::collection::vec_deque(any::<usize>().prop_map(Item::Pure), 1..5),
+            operation(),
+            any::<(usize, usize, usize)>(),
+        )
+            .prop_map(
+                |(id, items, operation, (divider, monkey_if_true, monkey_if_false))| Monkey {
+                    id,
+                    items,
+                    operation,
+                    throw_fetch: ThrowFetch {
+                        divider,
+                        monkey_if_true,
+                        monkey_if_false,
+                    },
+                },
+            )
+    }
+
+    proptest! {
+        #[test]
+        fn it_parses_back_its_formatted_input(monkeys in prop::collection::vec(monkey(), 1..5)) {
+            let input = Parser::format(&monkeys);
+            prop_assert_eq!(Parser::parse(input.as_bytes()), Ok(monkeys));
+        }
     }
 }
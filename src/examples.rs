@@ -0,0 +1,18 @@
+//! Regression tests on the example of each day, generated by the build script from `inputs/<day>.example` and
+//! `answers/<day>.example.toml`.
+
+use crate::{
+    problem::{Params, Part},
+    registry,
+    run_all::Expected,
+};
+
+fn check(day: usize, part: usize, input: &str, answers: &str) {
+    let solver = registry::find(day).unwrap();
+    let lines = crate::input::read_input(input.to_string()).unwrap();
+    let expected = Expected::read(answers).unwrap().part(part - 1);
+    let answer = (solver.solve)(&lines, &[Part::One, Part::Two][part - 1], &Params::default()).unwrap().answer;
+    assert_eq!(Some(answer), expected, "day {} part {}", day, part);
+}
+
+include!(concat!(env!("OUT_DIR"), "/examples.rs"));
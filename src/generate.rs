@@ -0,0 +1,75 @@
+//! Seeded generation of puzzle inputs far larger than the real ones, for the days whose solvers may not scale.
+//! Each such day exposes a `generate(seed, size)` giving the lines of a valid input.
+
+use std::ops::RangeInclusive;
+
+/// Pseudo-random generator (xorshift64*), so that a seed always gives the same input without depending on `rand`.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // The state must never be 0, which xorshift would keep forever.
+        Self(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Uniform in `range`, which must not be empty.
+    pub fn range(&mut self, range: RangeInclusive<usize>) -> usize {
+        let span = (range.end() - range.start()) as u64 + 1;
+        range.start() + (self.next_u64() % span) as usize
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.range(0..=items.len() - 1)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        day01, day05, day07, day08, day09, day11,
+        problem::{Params, Part},
+        registry,
+    };
+
+    #[test]
+    fn it_repeats_itself_for_a_seed() {
+        let draw = |seed| {
+            let mut rng = Rng::new(seed);
+            (0..100).map(|_| rng.range(3..=7)).collect::<Vec<_>>()
+        };
+        assert_eq!(draw(42), draw(42));
+        assert_ne!(draw(42), draw(43));
+        assert!(draw(0).iter().all(|n| (3..=7).contains(n)));
+    }
+
+    #[test]
+    fn it_generates_inputs_with_known_answers() {
+        type Generator = fn(u64, usize) -> Vec<String>;
+        let generators: [(usize, Generator, usize, [&str; 2]); 6] = [
+            (1, day01::generate, 100, ["314639", "906192"]),
+            (5, day05::generate, 100, ["ORGBFIXC", "CPIXHJBR"]),
+            (7, day07::generate, 100, ["176399", "10266750"]),
+            (8, day08::generate, 50, ["522", "74088"]),
+            (9, day09::generate, 100, ["794", "403"]),
+            (11, day11::generate, 10, ["194028", "67567763828"]),
+        ];
+        for (day, generate, size, expected) in generators {
+            let solve = registry::find(day).unwrap().solve;
+            let answers = [Part::One, Part::Two].map(|part| {
+                solve(&generate(7, size), &part, &Params::default())
+                    .unwrap()
+                    .answer
+                    .to_string()
+            });
+            assert_eq!(answers, expected, "Day {}", day);
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use std::io::{self, BufRead, BufReader};
+
+use crate::{
+    parse,
+    problem::{Error, ParsingError},
+};
+
+/// Default input of `day`, `inputs/<day>` or `inputs/<day>.example`.
+pub fn input_file(day: usize, example: bool) -> String {
+    let ext = match example {
+        false => "",
+        true => ".example",
+    };
+    format!("inputs/{}{}", day, ext)
+}
+
+/// Reads the lines of `file_name`, `-` standing for the standard input.
+pub fn read_input(file_name: String) -> Result<Vec<String>, Error> {
+    let reader: Box<dyn BufRead> = match file_name.as_str() {
+        "-" => Box::new(io::stdin().lock()),
+        _ => {
+            let file = std::fs::File::open(&file_name)
+                .map_err(|e| Error::CantOpenInputFile(file_name.clone(), e.to_string()))?;
+            Box::new(BufReader::new(file))
+        }
+    };
+
+    reader
+        .lines()
+        .collect::<Result<Vec<_>, io::Error>>()
+        .map_err(|e| Error::CantReadInput(file_name, e.to_string()))
+}
+
+/// Renders the parsing errors of `error` under the `input` lines they come from.
+pub fn render_parsing_errors(error: &Error, input: &[String]) {
+    match error {
+        Error::ParsingFailed(ParsingError::Parse(e)) => eprintln!("{}\n", e.render(input)),
+        Error::ParsingFailed(ParsingError::Many(errors)) => {
+            errors.iter().for_each(|e| eprintln!("{}\n", e.render(input)))
+        }
+        _ => (),
+    }
+}
+
+/// Renders `warnings` under the `input` lines they come from.
+pub fn render_warnings(warnings: &[parse::Error], input: &[String]) {
+    warnings.iter().for_each(|w| eprintln!("{}\n", w.render_warning(input)))
+}
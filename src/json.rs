@@ -0,0 +1,79 @@
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::problem::{check_params, check_part, solve_part, Error, Params, ParsingError, Part, Problem};
+
+pub fn micros(duration: Duration) -> u64 {
+    duration.as_micros() as u64
+}
+
+/// An error with its kind and message, along with the located parse errors behind a parsing failure.
+pub fn error(e: &Error) -> Value {
+    let kind = match e {
+        Error::CantOpenInputFile(_, _) => "cant_open_input_file",
+        Error::CantReadInput(_, _) => "cant_read_input",
+        Error::ParsingFailed(_) => "parsing_failed",
+        Error::NoCorrespondingSolver(_) => "no_corresponding_solver",
+        Error::NoSuchPart(_, _) => "no_such_part",
+        Error::NoSuchParam(_, _) => "no_such_param",
+        Error::DaysFailed(_) => "days_failed",
+        Error::InputsFailed(_) => "inputs_failed",
+        Error::SolverFailed(_) => "solver_failed",
+    };
+    let located = match e {
+        Error::ParsingFailed(ParsingError::Parse(e)) => vec![e.clone()],
+        Error::ParsingFailed(ParsingError::Many(errors)) => errors.clone(),
+        _ => vec![],
+    };
+    json!({ "kind": kind, "message": e.to_string(), "parse_errors": located })
+}
+
+/// Report of `part` of a problem: its answer, or the parsed problem itself for part 0, with the warnings raised
+/// while parsing it and timings.
+pub fn report<T: Problem + Serialize>(lines: &[String], part: &Part, params: &Params) -> Value {
+    if let Err(e) = check_part::<T>(part).and_then(|()| check_params::<T>(params)) {
+        return json!({ "error": error(&e) });
+    }
+    let start = Instant::now();
+    let problem = match T::parse_with(lines, params) {
+        Ok(problem) => problem,
+        Err(e) => return json!({ "error": error(&Error::ParsingFailed(e)) }),
+    };
+    let parse_us = micros(start.elapsed());
+    let warnings = problem.warnings();
+    if part == &Part::Parsed {
+        return json!({ "parsed": problem, "warnings": warnings, "parse_us": parse_us });
+    }
+
+    let start = Instant::now();
+    let answer = solve_part(&problem, part);
+    let solve_us = micros(start.elapsed());
+    match answer {
+        Ok(answer) => json!({ "answer": answer, "warnings": warnings, "parse_us": parse_us, "solve_us": solve_us }),
+        Err(e) => json!({ "error": error(&e), "warnings": warnings, "parse_us": parse_us, "solve_us": solve_us }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{day01::Inventories, problem::Answer};
+
+    use super::*;
+
+    #[test]
+    fn it_reports_answers_and_located_parse_errors() {
+        let lines = |input: &str| input.lines().map(String::from).collect::<Vec<_>>();
+        let ok = report::<Inventories>(&lines("1000\n2000\n\n4000"), &Part::One, &Params::default());
+        assert_eq!(ok["answer"], json!({ "kind": "integer", "value": 4000 }));
+
+        let large = Answer::from(u64::MAX);
+        assert_eq!(large.to_string(), "18446744073709551615");
+        assert_eq!(json!(large), json!({ "kind": "integer", "value": u64::MAX }));
+
+        let err = report::<Inventories>(&lines("1000\nx"), &Part::One, &Params::default());
+        assert_eq!(err["error"]["kind"], "parsing_failed");
+        assert_eq!(err["error"]["parse_errors"][0]["start"], json!({ "line": 2, "col": 1 }));
+    }
+}
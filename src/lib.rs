@@ -0,0 +1,43 @@
+//! Solutions to Advent of Code 2022, along with the parser combinators they are built on.
+//!
+//! Each day implements `Problem` and is registered in `registry::SOLVERS`; `prelude` gathers what a day needs.
+
+// Lets `#[derive(Parse)]` name this crate the same way from inside and from its dependents.
+extern crate self as aoc_2022;
+
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+pub mod day10;
+pub mod day11;
+#[cfg(test)]
+mod examples;
+pub mod generate;
+pub mod input;
+pub mod json;
+pub mod parse;
+pub mod problem;
+pub mod registry;
+pub mod run_all;
+
+pub mod prelude {
+    pub use crate::parse::{
+        capture::Capture,
+        couple::Couple,
+        either::Either,
+        grid::{Grid, Matrix},
+        natural::Natural,
+        separator::{EmptyLineSep, LineSep, SpaceSep, StrSep},
+        seq::{Seq, SeqRecover},
+        table::Table,
+        Context, Format, Parse, ParseExt, StaticStr,
+    };
+    pub use crate::problem::*;
+    pub use crate::DefStaticStr;
+}
@@ -1,89 +1,178 @@
-#![allow(incomplete_features)]
-#![feature(adt_const_params)]
-#![feature(iter_array_chunks)]
-
-use std::io::{self, BufRead, BufReader};
+use std::str::FromStr;
 use structopt::StructOpt;
 
-mod day01;
-mod day02;
-mod day03;
-mod day04;
-
-mod day05;
-mod day06;
-mod day07;
-mod day08;
-
-mod day09;
-mod day10;
-mod day11;
-mod parse;
-mod problem;
-use problem::Error;
-
-pub mod prelude {
-    pub use crate::parse::{
-        capture::Capture,
-        couple::Couple,
-        either::Either,
-        natural::Natural,
-        separator::{EmptyLineSep, LineSep, SpaceSep, StrSep},
-        seq::Seq,
-        table::Table,
-        ParseExt,
-    };
-    pub use crate::problem::*;
+use aoc_2022::{
+    input::{input_file, read_input, render_parsing_errors, render_warnings},
+    json,
+    problem::{Answer, Error, Params, Part, Solver},
+    registry,
+    run_all::{self, OutputFormat},
+};
+
+/// Day to solve: either its number or `all` for every registered one.
+enum Days {
+    One(usize),
+    All,
+}
+
+impl FromStr for Days {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "all" => Ok(Days::All),
+            _ => s.parse().map(Days::One).map_err(|_| format!("'{}' is neither a day number nor 'all'", s)),
+        }
+    }
+}
+
+/// `<name>=<value>` setting given with `--param`.
+fn parse_param(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((name, value)) => Ok((name.to_string(), value.to_string())),
+        None => Err(format!("'{}' isn't of the form <name>=<value>", s)),
+    }
+}
+
+#[derive(StructOpt)]
+enum Command {
+    /// Runs both parts of every day, checking their answers against the ones stored in `answers/`
+    RunAll {
+        #[structopt(long)]
+        example: bool,
+    },
 }
 
 #[derive(StructOpt)]
+#[structopt(setting = structopt::clap::AppSettings::SubcommandsNegateReqs)]
 struct Opt {
-    day: usize,
-    part: usize,
+    #[structopt(subcommand)]
+    command: Option<Command>,
+    /// Lists the registered solvers instead of solving a day
     #[structopt(long)]
-    input: Option<String>,
+    list: bool,
+    #[structopt(required_unless = "list")]
+    day: Option<Days>,
+    /// 1 or 2, 0 for the parsed input, or the name of an extra part listed by --list
+    #[structopt(required_unless = "list")]
+    part: Option<Part>,
+    /// Input file instead of `inputs/<day>`, `-` for the standard input. Each one repeated is solved in turn
+    #[structopt(long, number_of_values = 1)]
+    input: Vec<String>,
     #[structopt(long, conflicts_with = "input")]
     example: bool,
+    /// Setting of the day as `<name>=<value>`, such as `unknown-commands=warn` for day 7, listed by --list. Can be
+    /// repeated
+    #[structopt(long, number_of_values = 1, parse(try_from_str = parse_param))]
+    param: Vec<(String, String)>,
+    /// Either `text` or `json`
+    #[structopt(long, global = true, default_value = "text")]
+    format: OutputFormat,
+}
+
+/// Solves `part` of `solver` on the input read from `file_name`, parsing errors being rendered on the way.
+fn run(solver: &Solver, file_name: String, part: &Part, params: &Params) -> Result<Answer, Error> {
+    let lines = read_input(file_name)?;
+    let solved = (solver.solve)(&lines, part, params).inspect_err(|e| render_parsing_errors(e, &lines))?;
+    render_warnings(&solved.warnings, &lines);
+    Ok(solved.answer)
+}
+
+/// Same as `run`, reported as JSON.
+fn report(solver: &Solver, file_name: String, part: &Part, params: &Params) -> serde_json::Value {
+    let mut report = match read_input(file_name.clone()) {
+        Ok(lines) => (solver.report)(&lines, part, params),
+        Err(e) => serde_json::json!({ "error": json::error(&e) }),
+    };
+    report["input"] = file_name.into();
+    report["day"] = solver.day.into();
+    report["part"] = part.to_string().into();
+    report
 }
 
 fn main_bis() -> Result<(), Error>
 {
     let opt = Opt::from_args();
 
-    let file_name = match opt.input {
-        None => {
-            let ext = match opt.example {
-                false => "",
-                true => ".example",
-            };
-            format!("inputs/{}{}", opt.day, ext)
+    if let Some(Command::RunAll { example }) = opt.command {
+        return run_all::run_all(example, opt.format);
+    }
+
+    if opt.list {
+        for solver in registry::SOLVERS {
+            println!("{:>2}  {:<24} {} / {}", solver.day, solver.title, solver.answers[0], solver.answers[1]);
+            for (name, description) in (solver.extra_parts)() {
+                println!("    {:<26} {}", name, description);
+            }
+            for (name, description) in (solver.params)() {
+                println!("    {:<26} {}", format!("--param {}", name), description);
+            }
+        }
+        return Ok(());
+    }
+
+    let (day, part) = (opt.day.unwrap(), opt.part.unwrap());
+    let params: Params = opt.param.into_iter().collect();
+    if matches!(day, Days::All) && !opt.input.is_empty() {
+        let description = "--input can't be used to solve all days";
+        structopt::clap::Error::with_description(description, structopt::clap::ErrorKind::ArgumentConflict).exit();
+    }
+
+    // Each solver with the input it runs on.
+    let runs: Vec<(&Solver, String)> = match day {
+        Days::One(day) if opt.input.is_empty() => vec![(registry::find(day)?, input_file(day, opt.example))],
+        Days::One(day) => {
+            let solver = registry::find(day)?;
+            opt.input.into_iter().map(|input| (solver, input)).collect()
         }
-        Some(file_name) => file_name,
+        Days::All => registry::SOLVERS.iter().map(|solver| (solver, input_file(solver.day, opt.example))).collect(),
     };
+    // A single run is printed as is, while answers of several ones are labelled by day or by input.
+    let single = matches!(day, Days::One(_)) && runs.len() == 1;
 
-    let file = std::fs::File::open(&file_name)
-        .map_err(|e| Error::CantOpenInputFile(file_name, e.to_string()))?;
-
-    let lines = BufReader::new(file)
-        .lines()
-        .collect::<Result<Vec<_>, io::Error>>()
-        .unwrap();
-
-    let solution: Result<String, problem::Error> = match opt.day {
-        1 => problem::solve::<day01::Inventories>(lines, opt.part),
-        2 => problem::solve::<day02::Guide>(lines, opt.part),
-        3 => problem::solve::<day03::RuckSacks>(lines, opt.part),
-        4 => problem::solve::<day04::AssignmentsPairs>(lines, opt.part),
-        5 => problem::solve::<day05::RearrangementProcedure>(lines, opt.part),
-        6 => problem::solve::<day06::Signal>(lines, opt.part),
-        7 => problem::solve::<day07::FileSystem>(lines, opt.part),
-        8 => problem::solve::<day08::Forest>(lines, opt.part),
-        9 => problem::solve::<day09::Movements>(lines, opt.part),
-        10 => problem::solve::<day10::Program>(lines, opt.part),
-        11 => problem::solve::<day11::MonkeyBehaviors>(lines, opt.part),
-        _ => Err(Error::NoCorrespondingSolver(opt.day)),
+    let failed: Vec<&(&Solver, String)> = if opt.format == OutputFormat::Json {
+        let reports: Vec<_> = runs
+            .iter()
+            .map(|(solver, file_name)| report(solver, file_name.clone(), &part, &params))
+            .collect();
+        let failed = runs
+            .iter()
+            .zip(&reports)
+            .filter(|(_, report)| report.get("error").is_some())
+            .map(|(entry, _)| entry)
+            .collect();
+        match single {
+            true => println!("{:#}", reports[0]),
+            false => println!("{:#}", serde_json::Value::from(reports)),
+        }
+        failed
+    } else {
+        let mut failed = vec![];
+        for entry in &runs {
+            let (solver, file_name) = entry;
+            let label = match day {
+                Days::One(_) => file_name.clone(),
+                Days::All => format!("Day {:>2} - {}", solver.day, solver.title),
+            };
+            match run(solver, file_name.clone(), &part, &params) {
+                Ok(answer) if single => println!("{}", answer),
+                Ok(answer) => println!("{}: {}", label, answer),
+                Err(e) if single => return Err(e),
+                Err(e) => {
+                    eprintln!("{}: {}", label, e);
+                    failed.push(entry);
+                }
+            }
+        }
+        failed
     };
-    println!("{}", solution?);
+
+    if !failed.is_empty() {
+        return Err(match day {
+            Days::One(_) => Error::InputsFailed(failed.iter().map(|(_, file_name)| file_name.clone()).collect()),
+            Days::All => Error::DaysFailed(failed.iter().map(|(solver, _)| solver.day).collect()),
+        });
+    }
 
     Ok(())
 
@@ -1,4 +1,5 @@
-use super::{Context, Error, Parse, ParseExt, StaticStr};
+use super::{Context, Error, Format, Fresh, Parse, StaticStr, SubParser};
+use std::{borrow::Cow, marker::PhantomData};
 
 #[derive(PartialEq, Eq)]
 enum PatternPart {
@@ -9,30 +10,35 @@ enum PatternPart {
 
 use PatternPart::*;
 
-pub struct Capture<S: StaticStr, const N: usize, T: Parse + Default> {
-    s: std::marker::PhantomData<S>,
+#[derive(PartialEq, Eq)]
+enum Step {
+    InPlaceHolder,
+    EndOfPlaceHolder,
+    Const,
+}
+
+/// Position reached in a pattern where each `%` is a placeholder.
+#[derive(Debug, Clone)]
+struct Cursor {
+    pattern: Cow<'static, str>,
     capture_pos: usize,
-    previous_context: Context,
-    accepted: Vec<u8>,
-    res: Vec<T::Out>,
 }
 
-impl<S: StaticStr, const N: usize, T: Parse + Default> Default for Capture<S, N, T> {
-    fn default() -> Self {
+impl Cursor {
+    fn new(pattern: impl Into<Cow<'static, str>>) -> Self {
         Self {
-            s:Default::default(),
-            capture_pos: Default::default(),
-            previous_context: Default::default(),
-            accepted: Default::default(),
-            res: Default::default(),
+            pattern: pattern.into(),
+            capture_pos: 0,
         }
     }
-}
 
-impl<S: StaticStr, const N: usize, T: Parse + Default> Capture<S, N, T> {
+    fn fresh(&self) -> Self {
+        Self::new(self.pattern.clone())
+    }
+
     fn pattern_part(&self) -> PatternPart {
-        if self.capture_pos < S::as_str().len() {
-            match S::as_str().as_bytes()[self.capture_pos] {
+        if self.capture_pos < self.pattern.len() {
+            match self.pattern.as_bytes()[self.capture_pos] {
                 b'%' => PlaceHolder,
                 _ => Const,
             }
@@ -40,67 +46,321 @@ impl<S: StaticStr, const N: usize, T: Parse + Default> Capture<S, N, T> {
             End
         }
     }
-}
 
-impl<S: StaticStr, const N: usize, T: Parse + Default> Parse for Capture<S, N, T> {
-    type Out = [T::Out; N];
+    fn at_start(&self) -> bool {
+        self.capture_pos == 0
+    }
 
-    fn read_byte(&mut self, byte: &u8, context: Context) -> Result<(), Error> {
+    fn at_placeholder(&self) -> bool {
+        self.pattern_part() == PlaceHolder
+    }
+
+    /// Moves over `byte`, a placeholder ending on the first byte matching the constant part following it.
+    fn step(&mut self, byte: &u8, context: Context) -> Result<Step, Error> {
         match self.pattern_part() {
-            PatternPart::PlaceHolder => {
-                if self.capture_pos + 1 <= S::as_str().len() - 1 && S::as_str().as_bytes()[self.capture_pos + 1] == *byte {
+            PlaceHolder => {
+                if self.capture_pos + 1 < self.pattern.len() && self.pattern.as_bytes()[self.capture_pos + 1] == *byte {
                     self.capture_pos += 2;
-                    let item = T::parse_with_context(&self.accepted, self.previous_context)?;
-                    self.res.push(item);
-                    self.accepted.clear();
+                    Ok(Step::EndOfPlaceHolder)
                 } else {
-                    self.accepted.push(*byte);
+                    Ok(Step::InPlaceHolder)
                 }
-                Ok(())
             }
-            PatternPart::Const => {
-                let expected = S::as_str().as_bytes()[self.capture_pos];
+            Const => {
+                let expected = self.pattern.as_bytes()[self.capture_pos];
                 if expected != *byte {
                     Err(Error::new(
-                        format!("{:?}", *byte),
-                        format!("Expected {} => {:?}", self.capture_pos, expected),
-                        context.line,
-                    ))
+                        context,
+                        context,
+                        format!("Expected '{}'", (expected as char).escape_debug()),
+                    )
+                    .found([*byte])
+                    .within("Capture"))
                 } else {
                     self.capture_pos += 1;
-                    if self.capture_pos == S::as_str().len() {
-                    } else if S::as_str().as_bytes()[self.capture_pos] == b'%' {
-                        self.previous_context = context;
-                    }
-                    Ok(())
+                    Ok(Step::Const)
                 }
             }
-            End => Err(Error::new("", "Reached end of pattern", context.line)),
+            End => Err(Error::new(context, context, "Reached end of pattern")
+                .found([*byte])
+                .within("Capture")),
         }
     }
 
-    fn end(mut self, context: Context) -> Result<Self::Out, Error> {
+    /// Checks the whole pattern was read, telling if its final placeholder is still to be ended.
+    fn end(&self, context: Context) -> Result<bool, Error> {
         match self.pattern_part() {
-            Const => Err(Error::new("", "Premature end of input", context.line)),
-            PlaceHolder if self.capture_pos == S::as_str().len() - 1 => {
-                let item = T::parse_with_context(&self.accepted, self.previous_context)?;
-                self.res.push(item);
-                Ok(self.res)
+            PlaceHolder if self.capture_pos == self.pattern.len() - 1 => Ok(true),
+            Const | PlaceHolder => Err(Error::new(context, context, "Premature end of input").within("Capture")),
+            End => Ok(false),
+        }
+    }
+}
+
+pub struct Capture<S: StaticStr, const N: usize, T: Parse + Default> {
+    s: PhantomData<S>,
+    inner: CaptureParser<N, T>,
+}
+
+impl<S: StaticStr, const N: usize, T: Parse + Default> Default for Capture<S, N, T> {
+    fn default() -> Self {
+        Self {
+            s: Default::default(),
+            inner: CaptureParser::new(S::as_str(), T::default()),
+        }
+    }
+}
+
+impl<S: StaticStr, const N: usize, T: Parse + Default> Parse for Capture<S, N, T> {
+    type Out = [T::Out; N];
+
+    fn read_byte(&mut self, byte: &u8, context: Context) -> Result<(), Error> {
+        self.inner.read_byte(byte, context)
+    }
+
+    fn end(self, context: Context) -> Result<Self::Out, Error> {
+        self.inner.end(context)
+    }
+}
+
+/// Writes `pattern`, with each placeholder replaced by the slot of the same index.
+fn write_pattern(pattern: &str, buffer: &mut String, mut write_slot: impl FnMut(usize, &mut String)) {
+    for (slot, part) in pattern.split('%').enumerate() {
+        if slot > 0 {
+            write_slot(slot - 1, buffer);
+        }
+        buffer.push_str(part);
+    }
+}
+
+impl<S: StaticStr, const N: usize, T: Format + Default> Format for Capture<S, N, T> {
+    fn write(out: &Self::Out, buffer: &mut String) {
+        write_pattern(S::as_str(), buffer, |slot, buffer| T::write(&out[slot], buffer))
+    }
+}
+
+/// Value-level `Capture`, whose pattern and slot parser may be chosen at runtime.
+pub struct CaptureParser<const N: usize, T: Fresh> {
+    cursor: Cursor,
+    slot: SubParser<T>,
+    res: Vec<T::Out>,
+}
+
+impl<const N: usize, T: Fresh> CaptureParser<N, T> {
+    pub fn new(pattern: impl Into<Cow<'static, str>>, slot: T) -> Self {
+        Self {
+            cursor: Cursor::new(pattern),
+            slot: SubParser::new(slot),
+            res: Default::default(),
+        }
+    }
+
+    fn end_slot(&mut self, context: Context) -> Result<(), Error> {
+        let item = self
+            .slot
+            .end(context)
+            .map_err(|e| e.within(format!("Capture.{}", self.res.len())))?;
+        self.res.push(item);
+        Ok(())
+    }
+}
+
+impl<const N: usize, T: Fresh> Fresh for CaptureParser<N, T> {
+    fn fresh(&self) -> Self {
+        Self {
+            cursor: self.cursor.fresh(),
+            slot: self.slot.fresh(),
+            res: Default::default(),
+        }
+    }
+}
+
+impl<const N: usize, T: Fresh> Parse for CaptureParser<N, T> {
+    type Out = [T::Out; N];
+
+    fn read_byte(&mut self, byte: &u8, context: Context) -> Result<(), Error> {
+        if self.cursor.at_start() {
+            self.slot.begin(byte, context);
+        }
+        match self.cursor.step(byte, context)? {
+            Step::InPlaceHolder => {
+                return self
+                    .slot
+                    .read_byte(byte, context)
+                    .map_err(|e| e.within(format!("Capture.{}", self.res.len())))
+            }
+            Step::EndOfPlaceHolder => self.end_slot(context)?,
+            Step::Const => (),
+        }
+        if self.cursor.at_placeholder() {
+            self.slot.restart(context);
+        }
+        Ok(())
+    }
+
+    fn end(mut self, context: Context) -> Result<Self::Out, Error> {
+        if self.cursor.end(context)? {
+            self.end_slot(context)?;
+        }
+        self.res
+            .try_into()
+            .map_err(|_| Error::new(context, context, "Not expected number of captured string").within("Capture"))
+    }
+}
+
+/// Tuple of parsers, one per placeholder of a `CaptureT` pattern.
+pub trait Slots {
+    type State: Default;
+    type Out;
+    const COUNT: usize;
+    fn begin(state: &mut Self::State, slot: usize, byte: &u8, context: Context);
+    fn restart(state: &mut Self::State, slot: usize, context: Context);
+    fn read_byte(state: &mut Self::State, slot: usize, byte: &u8, context: Context) -> Result<(), Error>;
+    fn end(state: &mut Self::State, slot: usize, context: Context) -> Result<(), Error>;
+    fn finish(state: Self::State) -> Option<Self::Out>;
+}
+
+/// `Slots` whose parsers can all write their output back.
+pub trait FormatSlots: Slots {
+    fn write_slot(out: &Self::Out, slot: usize, buffer: &mut String);
+}
+
+macro_rules! impl_slots {
+    ($count:literal; $($p:ident $i:tt),+) => {
+        impl<$($p: Parse + Default),+> Slots for ($($p,)+) {
+            type State = ($((SubParser<$p>, Option<$p::Out>),)+);
+            type Out = ($($p::Out,)+);
+            const COUNT: usize = $count;
+
+            fn begin(state: &mut Self::State, slot: usize, byte: &u8, context: Context) {
+                match slot {
+                    $($i => state.$i.0.begin(byte, context),)+
+                    _ => (),
+                }
+            }
+
+            fn restart(state: &mut Self::State, slot: usize, context: Context) {
+                match slot {
+                    $($i => state.$i.0.restart(context),)+
+                    _ => (),
+                }
+            }
+
+            fn read_byte(state: &mut Self::State, slot: usize, byte: &u8, context: Context) -> Result<(), Error> {
+                match slot {
+                    $($i => state.$i.0.read_byte(byte, context),)+
+                    _ => Err(Error::new(context, context, "More placeholders than captured parsers")),
+                }
+            }
+
+            fn end(state: &mut Self::State, slot: usize, context: Context) -> Result<(), Error> {
+                match slot {
+                    $($i => state.$i.1 = Some(state.$i.0.end(context)?),)+
+                    _ => return Err(Error::new(context, context, "More placeholders than captured parsers")),
+                }
+                Ok(())
+            }
+
+            fn finish(state: Self::State) -> Option<Self::Out> {
+                Some(($(state.$i.1?,)+))
+            }
+        }
+
+        impl<$($p: Format + Default),+> FormatSlots for ($($p,)+) {
+            fn write_slot(out: &Self::Out, slot: usize, buffer: &mut String) {
+                match slot {
+                    $($i => $p::write(&out.$i, buffer),)+
+                    _ => (),
+                }
             }
-            PlaceHolder => Err(Error::new("", "Premature end of input", context.line)),
-            End => Ok(self.res),
         }
-        .and_then(|res| {
-            res.try_into()
-                .map_err(|_| Error::new("", "Not expected number of captured string", context.line))
-        })
+    };
+}
+
+impl_slots!(1; P0 0);
+impl_slots!(2; P0 0, P1 1);
+impl_slots!(3; P0 0, P1 1, P2 2);
+impl_slots!(4; P0 0, P1 1, P2 2, P3 3);
+impl_slots!(5; P0 0, P1 1, P2 2, P3 3, P4 4);
+impl_slots!(6; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5);
+impl_slots!(7; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6);
+impl_slots!(8; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7);
+
+/// Like `Capture`, with a parser per placeholder given as a tuple, e.g.
+/// `CaptureT<S, (Natural<usize>, Seq<Natural<usize>, CommaSep>)>` returns a `(usize, Vec<usize>)`.
+pub struct CaptureT<S: StaticStr, P: Slots> {
+    s: PhantomData<S>,
+    cursor: Cursor,
+    slot: usize,
+    state: P::State,
+}
+
+impl<S: StaticStr, P: Slots> Default for CaptureT<S, P> {
+    fn default() -> Self {
+        Self {
+            s: Default::default(),
+            cursor: Cursor::new(S::as_str()),
+            slot: Default::default(),
+            state: Default::default(),
+        }
+    }
+}
+
+impl<S: StaticStr, P: Slots> CaptureT<S, P> {
+    fn end_slot(&mut self, context: Context) -> Result<(), Error> {
+        P::end(&mut self.state, self.slot, context).map_err(|e| e.within(format!("Capture.{}", self.slot)))?;
+        self.slot += 1;
+        Ok(())
+    }
+}
+
+impl<S: StaticStr, P: Slots> Parse for CaptureT<S, P> {
+    type Out = P::Out;
+
+    fn read_byte(&mut self, byte: &u8, context: Context) -> Result<(), Error> {
+        if self.cursor.at_start() {
+            P::begin(&mut self.state, self.slot, byte, context);
+        }
+        match self.cursor.step(byte, context)? {
+            Step::InPlaceHolder => {
+                return P::read_byte(&mut self.state, self.slot, byte, context)
+                    .map_err(|e| e.within(format!("Capture.{}", self.slot)))
+            }
+            Step::EndOfPlaceHolder => self.end_slot(context)?,
+            Step::Const => (),
+        }
+        if self.cursor.at_placeholder() {
+            P::restart(&mut self.state, self.slot, context);
+        }
+        Ok(())
+    }
+
+    fn end(mut self, context: Context) -> Result<Self::Out, Error> {
+        if self.cursor.end(context)? {
+            self.end_slot(context)?;
+        }
+        match self.slot == P::COUNT {
+            true => P::finish(self.state),
+            false => None,
+        }
+        .ok_or_else(|| Error::new(context, context, "Not expected number of captured values").within("Capture"))
+    }
+}
+
+impl<S: StaticStr, P: FormatSlots> Format for CaptureT<S, P> {
+    fn write(out: &Self::Out, buffer: &mut String) {
+        write_pattern(S::as_str(), buffer, |slot, buffer| P::write_slot(out, slot, buffer))
     }
-    //
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::parse::natural::Natural;
+    use crate::parse::{
+        natural::Natural,
+        separator::LineSep,
+        seq::{Seq, SeqParser, SkipFinal},
+        Format, ParseExt, ParseValue,
+    };
 
     use super::*;
 
@@ -113,7 +373,6 @@ mod tests {
 
     #[test]
     fn it_parses_the_move_command() {
-
         type Parser = Capture<MoveFromTo, 3, Natural<usize>>;
         let bytes = "move 32 from 101 to 202".as_bytes();
         let vec = Parser::parse(bytes).unwrap();
@@ -134,4 +393,71 @@ mod tests {
         let vec = Parser::parse(bytes).unwrap();
         assert_eq!(vec![43], vec);
     }
+
+    #[test]
+    fn it_parses_with_a_pattern_chosen_at_runtime() {
+        let pattern = String::from("move % from % to %");
+        let parser = CaptureParser::<3, _>::new(pattern, Natural::<usize>::default());
+        assert_eq!(
+            parser.parse_value("move 32 from 101 to 202".as_bytes()),
+            Ok([32, 101, 202])
+        );
+
+        let parser = SeqParser::new(CaptureParser::<2, _>::new("% -> %", Natural::<u8>::default()), "\n");
+        let err = parser.parse_value("1 -> 2\n3 -> 400".as_bytes()).unwrap_err();
+        assert_eq!(err.path(), "Seq > Capture.1 > Natural<u8>");
+        assert_eq!((err.start().line(), err.start().col()), (2, 6));
+    }
+
+    #[test]
+    fn it_captures_typed_slots() {
+        type Parser = CaptureT<MoveFromTo, (Natural<usize>, Natural<u8>, Natural<String>)>;
+        let bytes = "move 32 from 101 to abc".as_bytes();
+        assert_eq!(Parser::parse(bytes), Ok((32, 101, "abc".to_string())));
+    }
+
+    #[test]
+    fn it_locates_errors_in_typed_slots() {
+        type Parser = Seq<CaptureT<MoveFromTo, (Natural<usize>, Natural<u8>, Natural<usize>)>, LineSep, SkipFinal>;
+        let err = Parser::parse("move 1 from 2 to 3\nmove 4 from 256 to 6\n".as_bytes()).unwrap_err();
+        assert_eq!(err.path(), "Seq > Capture.1 > Natural<u8>");
+        assert_eq!((err.start().line(), err.start().col(), err.end().col()), (2, 13, 15));
+    }
+
+    #[test]
+    fn it_fails_with_more_placeholders_than_parsers() {
+        type Parser = CaptureT<MoveFromTo, (Natural<usize>, Natural<usize>)>;
+        let err = Parser::parse("move 1 from 2 to 3".as_bytes()).unwrap_err();
+        assert_eq!(err.path(), "Capture.2");
+    }
+
+    #[derive(Debug, Clone, PartialEq, crate::parse::Parse)]
+    #[parse(pattern = "move {n} from {from} to {to}", format)]
+    struct Move {
+        to: u8,
+        from: u8,
+        n: usize,
+    }
+
+    #[test]
+    fn it_derives_a_parser_from_a_pattern() {
+        let bytes = "move 32 from 1 to 2".as_bytes();
+        assert_eq!(MoveParser::parse(bytes), Ok(Move { n: 32, from: 1, to: 2 }));
+
+        let err = MoveParser::parse("move 3 from 1000 to 2".as_bytes()).unwrap_err();
+        assert_eq!(err.path(), "Move.from > Natural<u8>");
+        assert_eq!((err.start().col(), err.end().col()), (13, 16));
+    }
+
+    #[test]
+    fn it_formats_back_captured_values() {
+        type Parser = Capture<MoveFromTo, 3, Natural<usize>>;
+        assert_eq!(Parser::format(&[32, 101, 202]), "move 32 from 101 to 202");
+
+        type TypedParser = CaptureT<MoveFromTo, (Natural<usize>, Natural<u8>, Natural<String>)>;
+        assert_eq!(TypedParser::format(&(1, 2, "x".to_string())), "move 1 from 2 to x");
+
+        let parsed = MoveParser::parse("move 32 from 1 to 2".as_bytes()).unwrap();
+        assert_eq!(MoveParser::format(&parsed), "move 32 from 1 to 2");
+    }
 }
@@ -0,0 +1,197 @@
+use std::marker::PhantomData;
+
+use super::{
+    separator::{Delimiter, Match, Matcher},
+    short_type_name, Context, Error, Format, Parse,
+};
+
+/// Set of ASCII bytes.
+pub trait CharClass {
+    fn contains(byte: &u8) -> bool;
+    fn name() -> &'static str;
+}
+
+/// Space, tab, carriage return, line feed or form feed.
+#[derive(Debug)]
+pub struct Whitespace {}
+impl CharClass for Whitespace {
+    fn contains(byte: &u8) -> bool {
+        byte.is_ascii_whitespace()
+    }
+
+    fn name() -> &'static str {
+        "whitespace"
+    }
+}
+
+#[derive(Debug)]
+pub struct Digit {}
+impl CharClass for Digit {
+    fn contains(byte: &u8) -> bool {
+        byte.is_ascii_digit()
+    }
+
+    fn name() -> &'static str {
+        "digit"
+    }
+}
+
+#[derive(Debug)]
+pub struct Letter {}
+impl CharClass for Letter {
+    fn contains(byte: &u8) -> bool {
+        byte.is_ascii_alphabetic()
+    }
+
+    fn name() -> &'static str {
+        "letter"
+    }
+}
+
+/// One or more bytes of the class `C`, returned as a string.
+/// As a delimiter, it takes as many bytes of the class as it finds.
+#[derive(Debug)]
+pub struct Many1<C: CharClass> {
+    c: PhantomData<C>,
+    bytes: Vec<u8>,
+    start_context: Option<Context>,
+}
+
+pub type Digits = Many1<Digit>;
+pub type Alpha = Many1<Letter>;
+
+impl<C: CharClass> Default for Many1<C> {
+    fn default() -> Self {
+        Self {
+            c: Default::default(),
+            bytes: Default::default(),
+            start_context: Default::default(),
+        }
+    }
+}
+
+impl<C: CharClass> Parse for Many1<C> {
+    type Out = String;
+
+    fn read_byte(&mut self, byte: &u8, context: Context) -> Result<(), Error> {
+        self.start_context.get_or_insert(context);
+        if !C::contains(byte) {
+            return Err(Error::new(context, context, format!("Expected {}", C::name()))
+                .found([*byte])
+                .within(format!("Many1<{}>", short_type_name::<C>())));
+        }
+        self.bytes.push(*byte);
+        Ok(())
+    }
+
+    fn end(self, context: Context) -> Result<Self::Out, Error> {
+        if self.bytes.is_empty() {
+            return Err(
+                Error::new(context, context, format!("Expected at least one {}", C::name()))
+                    .within(format!("Many1<{}>", short_type_name::<C>())),
+            );
+        }
+        Ok(self.bytes.iter().map(|byte| *byte as char).collect())
+    }
+}
+
+impl<C: CharClass> Delimiter for Many1<C> {
+    type Matcher = Greedy<C>;
+
+    fn matcher() -> Greedy<C> {
+        Greedy::default()
+    }
+}
+
+/// Delimiter made of as many bytes of the class `C` as can be found, at least one.
+pub struct Greedy<C: CharClass> {
+    c: PhantomData<C>,
+}
+
+impl<C: CharClass> std::fmt::Debug for Greedy<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Greedy<{}>", short_type_name::<C>())
+    }
+}
+
+impl<C: CharClass> Default for Greedy<C> {
+    fn default() -> Self {
+        Self { c: Default::default() }
+    }
+}
+
+impl<C: CharClass> Clone for Greedy<C> {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl<C: CharClass> Matcher for Greedy<C> {
+    fn feed(&mut self, byte: &u8) -> Match {
+        match C::contains(byte) {
+            true => Match::Matched,
+            false => Match::Reject,
+        }
+    }
+
+    fn reset(&mut self) {}
+
+    fn describe(&self) -> String {
+        format!("one or more {}", C::name())
+    }
+}
+
+impl<C: CharClass> Format for Many1<C> {
+    fn write(out: &String, buffer: &mut String) {
+        buffer.push_str(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse::{
+        capture::CaptureT,
+        couple::Couple,
+        natural::Natural,
+        separator::LineSep,
+        seq::{Seq, Skip, SkipFinal},
+        ParseExt,
+    };
+
+    use super::*;
+
+    #[test]
+    fn it_splits_on_variable_whitespace() {
+        type Parser = Seq<Seq<Natural<usize>, Many1<Whitespace>, Skip>, LineSep, SkipFinal>;
+        let bytes = "1  2\t3\r\n 4 \t 5\r\n".as_bytes();
+        assert_eq!(Parser::parse(bytes), Ok(vec![vec![1, 2, 3], vec![4, 5]]));
+    }
+
+    #[test]
+    fn it_couples_around_whitespace() {
+        type Parser = Couple<Alpha, Many1<Whitespace>, Digits>;
+        assert_eq!(
+            Parser::parse("addx \t 15".as_bytes()),
+            Ok(("addx".to_string(), "15".to_string()))
+        );
+
+        let err = Parser::parse("addx 1 5".as_bytes()).unwrap_err();
+        assert_eq!(err.path(), "Couple");
+        assert_eq!((err.start().col(), err.end().col()), (7, 7));
+    }
+
+    DefStaticStr!(NameAge, "% is %");
+
+    #[test]
+    fn it_captures_classes() {
+        type Parser = CaptureT<NameAge, (Alpha, Digits)>;
+        assert_eq!(
+            Parser::parse("Bob is 42".as_bytes()),
+            Ok(("Bob".to_string(), "42".to_string()))
+        );
+
+        let err = Parser::parse("Bob is 4a".as_bytes()).unwrap_err();
+        assert_eq!(err.path(), "Capture.1 > Many1<Digit>");
+        assert_eq!(err.start().col(), 9);
+    }
+}
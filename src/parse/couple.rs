@@ -1,8 +1,11 @@
-use std::{collections::VecDeque, marker::PhantomData, str::from_utf8};
+use std::marker::PhantomData;
 
-use super::{separator::Separator, Context, Error, Parse, ParseExt};
+use super::{
+    separator::{Delimiter, Literal, Matcher, Piece, Separator, Splitter},
+    Context, Error, Format, Fresh, Parse, SubParser,
+};
 
-#[derive(PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SplitMode {
     Exact,
     First,
@@ -26,34 +29,24 @@ impl SplitModeLike for SplitFirst {
     }
 }
 
-#[derive(Debug)]
-pub struct Couple<T1: Parse + Default, S: Separator, T2: Parse + Default, SM: SplitModeLike = Exact> {
-    previous_context: Context,
-    p1: PhantomData<T1>,
-    p2: PhantomData<T2>,
+pub struct Couple<T1: Parse + Default, S: Delimiter, T2: Parse + Default, SM: SplitModeLike = Exact> {
     sep: PhantomData<S>,
     split_mode: PhantomData<SM>,
-    res: (Option<T1::Out>, Option<T2::Out>),
-    accepted: Vec<u8>,
-    potential: VecDeque<u8>,
+    inner: CoupleParser<T1, T2, S::Matcher>,
 }
 
-impl<T1: Parse + Default, T2: Parse + Default, S: Separator, SM: SplitModeLike> Default for Couple<T1, S, T2, SM> {
+impl<T1: Parse + Default, T2: Parse + Default, S: Delimiter, SM: SplitModeLike> Default for Couple<T1, S, T2, SM> {
     fn default() -> Self {
         Self {
-            previous_context: Default::default(),
-            p1: Default::default(),
-            p2: Default::default(),
             sep: Default::default(),
             split_mode: Default::default(),
-            res: Default::default(),
-            accepted: Default::default(),
-            potential: Default::default(),
+            inner: CoupleParser::with_delimiter(T1::default(), S::matcher(), T2::default())
+                .split_mode(SM::split_mode()),
         }
     }
 }
 
-impl<T1: Parse + Default, T2: Parse + Default, S: Separator, SM: SplitModeLike> Parse for Couple<T1, S, T2, SM>
+impl<T1: Parse + Default, T2: Parse + Default, S: Delimiter, SM: SplitModeLike> Parse for Couple<T1, S, T2, SM>
 where
     T1::Out: std::fmt::Debug,
     T2::Out: std::fmt::Debug,
@@ -61,51 +54,149 @@ where
     type Out = (T1::Out, T2::Out);
 
     fn read_byte(&mut self, byte: &u8, context: Context) -> Result<(), Error> {
-        self.potential.push_back(*byte);
-        if self.potential.len() == S::as_bytes().len() {
-            if self.potential.make_contiguous() == S::as_bytes() {
-                if self.res.0.is_none() {
-                    let item = T1::parse_with_context(&self.accepted, self.previous_context)?;
-                    self.res.0 = Some(item);
-                    self.potential.clear();
-                    self.accepted.clear();
-                } else if SM::split_mode() == SplitMode::Exact {
-                    return Err(Error::new("More than one field", "", context.line));
-                }
-            } else if let Some(byte) = self.potential.pop_front() {
-                if self.accepted.is_empty() {
-                    self.previous_context = context
+        self.inner.read_byte(byte, context)
+    }
+
+    fn end(self, context: Context) -> Result<Self::Out, Error> {
+        self.inner.end(context)
+    }
+}
+
+impl<T1: Format + Default, T2: Format + Default, S: Separator, SM: SplitModeLike> Format for Couple<T1, S, T2, SM>
+where
+    T1::Out: std::fmt::Debug,
+    T2::Out: std::fmt::Debug,
+{
+    fn write((left, right): &Self::Out, buffer: &mut String) {
+        T1::write(left, buffer);
+        buffer.push_str(&String::from_utf8_lossy(S::as_bytes()));
+        T2::write(right, buffer);
+    }
+}
+
+/// Value-level `Couple`, whose parsers and delimiter may be chosen at runtime.
+#[derive(Debug)]
+pub struct CoupleParser<T1: Fresh, T2: Fresh, M: Matcher = Literal> {
+    split_mode: SplitMode,
+    left: SubParser<T1>,
+    right: SubParser<T2>,
+    left_out: Option<T1::Out>,
+    splitter: Splitter<M>,
+}
+
+impl<T1: Fresh, T2: Fresh> CoupleParser<T1, T2, Literal> {
+    pub fn new(left: T1, separator: impl Into<Literal>, right: T2) -> Self {
+        Self::with_delimiter(left, separator.into(), right)
+    }
+}
+
+impl<T1: Fresh, T2: Fresh, M: Matcher> CoupleParser<T1, T2, M> {
+    pub fn with_delimiter(left: T1, matcher: M, right: T2) -> Self {
+        Self {
+            split_mode: SplitMode::Exact,
+            left: SubParser::new(left),
+            right: SubParser::new(right),
+            left_out: None,
+            splitter: Splitter::new(matcher),
+        }
+    }
+
+    pub fn split_mode(mut self, split_mode: SplitMode) -> Self {
+        self.split_mode = split_mode;
+        self
+    }
+
+    fn feed(
+        left: &mut SubParser<T1>,
+        right: &mut SubParser<T2>,
+        left_out: &Option<T1::Out>,
+        byte: &u8,
+        context: Context,
+    ) -> Result<(), Error> {
+        match left_out {
+            None => left.read_byte(byte, context).map_err(|e| e.within("Couple.left")),
+            Some(_) => right.read_byte(byte, context).map_err(|e| e.within("Couple.right")),
+        }
+    }
+
+    fn take(
+        left: &mut SubParser<T1>,
+        right: &mut SubParser<T2>,
+        left_out: &mut Option<T1::Out>,
+        split_mode: SplitMode,
+        piece: Piece,
+    ) -> Result<(), Error> {
+        match piece {
+            Piece::Byte(byte, context) => Self::feed(left, right, left_out, &byte, context),
+            Piece::Delimiter(delimiter) => {
+                let (start, context) = match (delimiter.first(), delimiter.last()) {
+                    (Some((_, start)), Some((_, end))) => (*start, *end),
+                    _ => Default::default(),
+                };
+                if left_out.is_none() {
+                    *left_out = Some(left.end(context).map_err(|e| e.within("Couple.left"))?);
+                    right.restart(context);
+                    Ok(())
+                } else if split_mode == SplitMode::Exact {
+                    let bytes: Vec<u8> = delimiter.iter().map(|(byte, _)| *byte).collect();
+                    Err(Error::new(start, context, "More than one field")
+                        .found(bytes)
+                        .within("Couple"))
+                } else {
+                    for (byte, context) in delimiter {
+                        Self::feed(left, right, left_out, &byte, context)?;
+                    }
+                    Ok(())
                 }
-                self.accepted.push(byte);
             }
         }
-        Ok(())
+    }
+}
+
+impl<T1: Fresh, T2: Fresh, M: Matcher> Fresh for CoupleParser<T1, T2, M> {
+    fn fresh(&self) -> Self {
+        Self {
+            split_mode: self.split_mode,
+            left: self.left.fresh(),
+            right: self.right.fresh(),
+            left_out: None,
+            splitter: self.splitter.fresh(),
+        }
+    }
+}
+
+impl<T1: Fresh, T2: Fresh, M: Matcher> Parse for CoupleParser<T1, T2, M> {
+    type Out = (T1::Out, T2::Out);
+
+    fn read_byte(&mut self, byte: &u8, context: Context) -> Result<(), Error> {
+        if self.left_out.is_none() {
+            self.left.begin(byte, context);
+        }
+        let (left, right, left_out, split_mode) =
+            (&mut self.left, &mut self.right, &mut self.left_out, self.split_mode);
+        self.splitter.push(byte, context, &mut |piece| {
+            Self::take(left, right, left_out, split_mode, piece)
+        })
     }
 
     fn end(mut self, context: Context) -> Result<Self::Out, Error> {
-        if !self.potential.is_empty() {
-            if self.potential == S::as_bytes() && SM::split_mode() == SplitMode::Exact {
+        let (left, right, left_out, split_mode) =
+            (&mut self.left, &mut self.right, &mut self.left_out, self.split_mode);
+        self.splitter
+            .finish(&mut |piece| Self::take(left, right, left_out, split_mode, piece))?;
+        let left = match self.left_out.take() {
+            Some(left) => left,
+            None => {
                 return Err(Error::new(
-                    "",
-                    format!("Final unexpected separator: '{:?}'", S::as_bytes()),
-                    0,
-                ));
-            } else {
-                self.accepted.extend(self.potential.to_owned())
+                    context,
+                    context,
+                    format!("Reached end without finding separator '{}'", self.splitter.describe()),
+                )
+                .within("Couple"))
             }
-        }
-        if self.res.0.is_none() {
-            return Err(Error::new(
-                from_utf8(&self.accepted).unwrap(),
-                format!("Reached end without finding separator: '{:?}'", S::as_bytes()),
-                context.line,
-            ));
-        }
-        match T2::parse_with_context(&self.accepted, context) {
-            Ok(item) => self.res.1 = Some(item),
-            Err(err) => return Err(err),
-        }
-        Ok((self.res.0.unwrap(), self.res.1.unwrap()))
+        };
+        let right = self.right.end(context).map_err(|e| e.within("Couple.right"))?;
+        Ok((left, right))
     }
 }
 
@@ -113,7 +204,7 @@ where
 mod tests {
 
     use super::*;
-    use crate::parse::{natural::Natural, separator::SpaceSep};
+    use crate::parse::{natural::Natural, separator::SpaceSep, ParseExt, ParseValue};
 
     #[test]
     fn it_parse_a_couple() {
@@ -128,4 +219,20 @@ mod tests {
         let couple = Couple::<Natural<usize>, SpaceSep, Natural<String>, SplitFirst>::parse(couple).unwrap();
         assert_eq!((200, "Coucou les loulous".to_string()), couple);
     }
+
+    #[test]
+    fn it_parses_a_couple_built_at_runtime() {
+        let parser = CoupleParser::new(Natural::<usize>::default(), " -> ", Natural::<String>::default());
+        assert_eq!(
+            parser.parse_value("1 -> a -> b".as_bytes()).unwrap_err().path(),
+            "Couple"
+        );
+
+        let parser = CoupleParser::new(Natural::<usize>::default(), " -> ", Natural::<String>::default())
+            .split_mode(SplitMode::First);
+        assert_eq!(
+            parser.parse_value("1 -> a -> b".as_bytes()),
+            Ok((1, "a -> b".to_string()))
+        );
+    }
 }
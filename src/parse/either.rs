@@ -1,6 +1,6 @@
-use std::{marker::PhantomData, str::from_utf8};
+use std::marker::PhantomData;
 
-use super::{Context, Error, Parse, ParseExt};
+use super::{one_of::furthest, Context, Error, Format, Parse, ParseExt};
 
 #[derive(Default)]
 pub struct Either<P1: Parse + Default, P2: Parse + Default> {
@@ -14,31 +14,43 @@ impl<P1: Parse + Default, P2: Parse + Default> Parse for Either<P1, P2> {
     type Out = either::Either<P1::Out, P2::Out>;
 
     fn read_byte(&mut self, byte: &u8, context: Context) -> Result<(), Error> {
-        self.buffer.push(*byte);
         if self.start_context.is_none() {
-            self.start_context = Some(context);
+            self.start_context = Some(context.preceding(byte));
         }
+        self.buffer.push(*byte);
         Ok(())
     }
 
     fn end(self, context: Context) -> Result<Self::Out, Error> {
-        if let Ok(p1_out) = P1::parse(&self.buffer) {
-            Ok(either::Either::Left(p1_out))
-        } else if let Ok(p2_out) = P2::parse(&self.buffer) {
-            Ok(either::Either::Right(p2_out))
-        } else {
-            Err(Error {
-                context: from_utf8(&self.buffer).unwrap().to_string(),
-                message: "Neither parser succeed".to_string(),
-                line_number: context.line,
-            })
+        let start = self.start_context.unwrap_or(context);
+        let left_err = match P1::parse_with_context(&self.buffer, start) {
+            Ok(p1_out) => return Ok(either::Either::Left(p1_out)),
+            Err(e) => e.within("Either.left"),
+        };
+        match P2::parse_with_context(&self.buffer, start) {
+            Ok(p2_out) => Ok(either::Either::Right(p2_out)),
+            Err(e) => Err(furthest(Some(left_err), e.within("Either.right"))),
+        }
+    }
+}
+
+impl<P1: Format + Default, P2: Format + Default> Format for Either<P1, P2> {
+    fn write(out: &Self::Out, buffer: &mut String) {
+        match out {
+            either::Either::Left(left) => P1::write(left, buffer),
+            either::Either::Right(right) => P2::write(right, buffer),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::parse::{couple::Couple, natural::Natural, separator::SpaceSep};
+    use crate::parse::{
+        couple::Couple,
+        natural::Natural,
+        separator::{LineSep, SpaceSep},
+        seq::Seq,
+    };
 
     use super::*;
 
@@ -50,4 +62,12 @@ mod tests {
             assert_eq!(Parser::parse(input.as_bytes()), Ok(expected));
         });
     }
+
+    #[test]
+    fn it_keeps_the_position_of_the_furthest_failure() {
+        type Parser = Seq<Either<Natural<usize>, Couple<Natural<usize>, SpaceSep, Natural<usize>>>, LineSep>;
+        let err = Parser::parse("1\n2 x".as_bytes()).unwrap_err();
+        assert_eq!(err.path(), "Seq > Either.right > Couple.right > Natural<usize>");
+        assert_eq!((err.start().line(), err.start().col(), err.end().col()), (2, 3, 3));
+    }
 }
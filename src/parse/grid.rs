@@ -0,0 +1,225 @@
+use super::{
+    separator::Separator,
+    table::Table,
+    write_separated, Context, Error, Format, Parse,
+};
+use std::marker::PhantomData;
+
+/// Rectangular grid of cells, stored row after row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Matrix<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Matrix<T> {
+    /// Builds a matrix from rows of the same length, `None` when they are not.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Self> {
+        let width = rows.first().map_or(0, Vec::len);
+        if rows.iter().any(|row| row.len() != width) {
+            return None;
+        }
+        Some(Self {
+            width,
+            height: rows.len(),
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        match col < self.width {
+            true => self.cells.get(row * self.width + col),
+            false => None,
+        }
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(|row| &self.cells[row * self.width..(row + 1) * self.width])
+    }
+
+    pub fn into_rows(self) -> Vec<Vec<T>> {
+        let mut cells = self.cells.into_iter();
+        (0..self.height)
+            .map(|_| cells.by_ref().take(self.width).collect())
+            .collect()
+    }
+}
+
+/// A parsed row of a `Grid`, with the span it was read from.
+pub struct Row<T> {
+    pub cells: Vec<T>,
+    pub start: Context,
+    pub end: Context,
+}
+
+/// What `Grid` does with rows that don't have as many cells as the others.
+pub trait Ragged<T> {
+    fn square(rows: Vec<Row<T>>) -> Result<Matrix<T>, Error>;
+}
+
+/// Rejects any row whose length differs from the first one.
+pub struct Strict {}
+impl<T> Ragged<T> for Strict {
+    fn square(rows: Vec<Row<T>>) -> Result<Matrix<T>, Error> {
+        let width = rows.first().map_or(0, |row| row.cells.len());
+        if let Some((index, row)) = rows.iter().enumerate().find(|(_, row)| row.cells.len() != width) {
+            return Err(Error::new(
+                row.start,
+                row.end,
+                format!(
+                    "Row has {} cells, expected {} like the first one",
+                    row.cells.len(),
+                    width
+                ),
+            )
+            .within(format!("Grid.{}", index)));
+        }
+        Ok(Matrix::from_rows(rows.into_iter().map(|row| row.cells).collect()).unwrap())
+    }
+}
+
+/// Fills the rows shorter than the longest one with default cells.
+pub struct Pad {}
+impl<T: Default> Ragged<T> for Pad {
+    fn square(rows: Vec<Row<T>>) -> Result<Matrix<T>, Error> {
+        let width = rows.iter().map(|row| row.cells.len()).max().unwrap_or(0);
+        let rows = rows.into_iter().map(|row| {
+            let mut cells = row.cells;
+            cells.resize_with(width, T::default);
+            cells
+        });
+        Ok(Matrix::from_rows(rows.collect()).unwrap())
+    }
+}
+
+/// Drops the cells past the length of the shortest row.
+pub struct Truncate {}
+impl<T> Ragged<T> for Truncate {
+    fn square(rows: Vec<Row<T>>) -> Result<Matrix<T>, Error> {
+        let width = rows.iter().map(|row| row.cells.len()).min().unwrap_or(0);
+        let rows = rows.into_iter().map(|row| {
+            let mut cells = row.cells;
+            cells.truncate(width);
+            cells
+        });
+        Ok(Matrix::from_rows(rows.collect()).unwrap())
+    }
+}
+
+/// Lines of `Table<N, S, T>` making up a `Matrix`, ragged rows being handled by `R`.
+/// Errors are labelled with the row then the cell that failed, e.g. `Grid.2 > Table.4 > Natural<usize>`.
+pub struct Grid<const N: usize, S: Separator, T: Parse + Default, R: Ragged<T::Out> = Strict> {
+    ragged: PhantomData<R>,
+    row: Table<N, S, T>,
+    row_start: Option<Context>,
+    row_end: Context,
+    rows: Vec<Row<T::Out>>,
+}
+
+impl<const N: usize, S: Separator, T: Parse + Default, R: Ragged<T::Out>> Default for Grid<N, S, T, R> {
+    fn default() -> Self {
+        Self {
+            ragged: Default::default(),
+            row: Default::default(),
+            row_start: Default::default(),
+            row_end: Default::default(),
+            rows: Default::default(),
+        }
+    }
+}
+
+impl<const N: usize, S: Separator, T: Parse + Default, R: Ragged<T::Out>> Grid<N, S, T, R> {
+    fn end_row(&mut self) -> Result<(), Error> {
+        let start = self.row_start.take().unwrap_or(self.row_end);
+        let end = self.row_end.max(start);
+        let label = format!("Grid.{}", self.rows.len());
+        let cells = std::mem::take(&mut self.row).end(end).map_err(|e| e.within(label))?;
+        self.rows.push(Row { cells, start, end });
+        Ok(())
+    }
+}
+
+impl<const N: usize, S: Separator, T: Parse + Default, R: Ragged<T::Out>> Parse for Grid<N, S, T, R> {
+    type Out = Matrix<T::Out>;
+
+    fn read_byte(&mut self, byte: &u8, context: Context) -> Result<(), Error> {
+        if byte == &b'\n' {
+            self.row_start.get_or_insert(context.preceding(byte));
+            return self.end_row();
+        }
+        self.row_start.get_or_insert(context);
+        self.row_end = context;
+        let label = format!("Grid.{}", self.rows.len());
+        self.row.read_byte(byte, context).map_err(|e| e.within(label))
+    }
+
+    fn end(mut self, _context: Context) -> Result<Self::Out, Error> {
+        if self.row_start.is_some() {
+            self.end_row()?;
+        }
+        R::square(self.rows)
+    }
+}
+
+impl<const N: usize, S: Separator, T: Format + Default, R: Ragged<T::Out>> Format for Grid<N, S, T, R> {
+    fn write(out: &Self::Out, buffer: &mut String) {
+        for (i, row) in out.rows().enumerate() {
+            if i > 0 {
+                buffer.push('\n');
+            }
+            write_separated::<T>(row, S::as_bytes(), buffer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse::{
+        natural::Natural,
+        separator::{Empty, Space, StrSep},
+        ParseExt,
+    };
+
+    use super::*;
+
+    #[test]
+    fn it_parses_a_rectangular_grid() {
+        type Parser = Grid<1, StrSep<Empty>, Natural<u8>>;
+        let grid = Parser::parse("123\n456".as_bytes()).unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.get(1, 0), Some(&4));
+        assert_eq!(grid.get(0, 3), None);
+        assert_eq!(grid.into_rows(), vec![vec![1, 2, 3], vec![4, 5, 6]]);
+    }
+
+    #[test]
+    fn it_handles_ragged_rows_according_to_the_policy() {
+        let input = "1 2 3\n4\n5 6".as_bytes();
+        let err = Grid::<1, StrSep<Space>, Natural<u8>, Strict>::parse(input).unwrap_err();
+        assert_eq!(err.path(), "Grid.1");
+        assert_eq!((err.start().line(), err.start().col(), err.end().col()), (2, 1, 1));
+
+        let padded = Grid::<1, StrSep<Space>, Natural<u8>, Pad>::parse(input).unwrap();
+        assert_eq!(padded.into_rows(), vec![vec![1, 2, 3], vec![4, 0, 0], vec![5, 6, 0]]);
+
+        let truncated = Grid::<1, StrSep<Space>, Natural<u8>, Truncate>::parse(input).unwrap();
+        assert_eq!(truncated.into_rows(), vec![vec![1], vec![4], vec![5]]);
+    }
+
+    #[test]
+    fn it_reports_the_failing_cell() {
+        type Parser = Grid<1, StrSep<Empty>, Natural<u8>>;
+        let err = Parser::parse("123\n456\n78x".as_bytes()).unwrap_err();
+        assert_eq!(err.path(), "Grid.2 > Table.2 > Natural<u8>");
+        assert_eq!((err.start().line(), err.start().col()), (3, 3));
+    }
+}
@@ -1,4 +1,4 @@
-use super::{Context, Error, Parse};
+use super::{Context, Error, Format, Parse};
 
 #[derive(Default)]
 pub struct Keep {
@@ -11,7 +11,7 @@ impl Parse for Keep {
 
     fn read_byte(&mut self, byte: &u8, context: Context) -> Result<(), Error> {
         if self.start_context.is_none() {
-            self.start_context = Some(context);
+            self.start_context = Some(context.preceding(byte));
         }
         self.bytes.push(*byte);
         Ok(())
@@ -21,3 +21,9 @@ impl Parse for Keep {
         Ok((self.bytes, self.start_context.unwrap_or(context), context))
     }
 }
+
+impl Format for Keep {
+    fn write((bytes, _, _): &Self::Out, buffer: &mut String) {
+        buffer.push_str(&String::from_utf8_lossy(bytes))
+    }
+}
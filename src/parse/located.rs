@@ -0,0 +1,52 @@
+use super::{Context, Error, Format, Parse};
+
+/// Output of `P` along with the span of the input it was read from, from its first byte to its last one past any
+/// trailing newline, e.g. to report a failure found once the whole input is parsed.
+#[derive(Default)]
+pub struct Located<P: Parse + Default> {
+    parser: P,
+    start: Option<Context>,
+    end: Context,
+}
+
+impl<P: Parse + Default> Parse for Located<P> {
+    type Out = (P::Out, Context, Context);
+
+    fn read_byte(&mut self, byte: &u8, context: Context) -> Result<(), Error> {
+        if byte != &b'\n' {
+            self.start.get_or_insert(context);
+            self.end = context;
+        }
+        self.parser.read_byte(byte, context)
+    }
+
+    fn end(self, context: Context) -> Result<Self::Out, Error> {
+        let start = self.start.unwrap_or(context);
+        let end = self.end.max(start);
+        Ok((self.parser.end(context)?, start, end))
+    }
+}
+
+impl<P: Format + Default> Format for Located<P> {
+    fn write((out, _, _): &Self::Out, buffer: &mut String) {
+        P::write(out, buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse::{natural::Natural, separator::LineSep, seq::Seq, ParseExt};
+
+    use super::*;
+
+    #[test]
+    fn it_gives_the_span_of_each_item() {
+        type Parser = Seq<Located<Natural<usize>>, LineSep>;
+        let items = Parser::parse("1\n234".as_bytes()).unwrap();
+        let spans: Vec<_> = items
+            .iter()
+            .map(|(_, start, end)| (start.line(), start.col(), end.col()))
+            .collect();
+        assert_eq!(spans, vec![(1, 1, 1), (2, 1, 3)]);
+    }
+}
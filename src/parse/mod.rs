@@ -1,6 +1,11 @@
 use derive_more::Display;
+use serde::Serialize;
+use std::io::{self, Read};
 
-#[derive(Debug, Copy, Clone)]
+/// Position of a byte in the parsed input: `line` starts at 1, `col` starts at 1 on the first byte of a line and a
+/// context with `col == 0` points just before the first byte of `line`.
+#[derive(Debug, Display, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[display(fmt = "{}:{}", line, col)]
 pub struct Context {
     line: usize,
     col: usize,
@@ -11,77 +16,488 @@ impl Default for Context {
     }
 }
 
+impl Context {
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn col(&self) -> usize {
+        self.col
+    }
+
+    fn advance(&mut self, byte: &u8) {
+        if byte == &0xA {
+            self.line += 1;
+            self.col = 0;
+        } else {
+            self.col += 1;
+        }
+    }
+
+    /// Context preceding a byte read at `self`, i.e. the one to start from when parsing again from this byte.
+    /// A preceding newline always resets the column, so its own column does not matter.
+    fn preceding(&self, byte: &u8) -> Self {
+        if byte == &0xA {
+            Self {
+                line: self.line - 1,
+                col: 0,
+            }
+        } else {
+            Self {
+                line: self.line,
+                col: self.col.saturating_sub(1),
+            }
+        }
+    }
+}
+
+pub use parse_derive::Parse;
+
 pub trait Parse {
     type Out;
     fn read_byte(&mut self, byte: &u8, context: Context) -> Result<(), Error>;
     fn end(self, context: Context) -> Result<Self::Out, Error>;
 }
 
+/// Inverse of a parser: writes an output back as an input that parses into it.
+pub trait Format: Parse {
+    fn write(out: &Self::Out, buffer: &mut String);
+
+    fn format(out: &Self::Out) -> String {
+        let mut buffer = String::new();
+        Self::write(out, &mut buffer);
+        buffer
+    }
+}
+
+/// Writes `items` with `T`, one after the other with `separator` between them.
+pub(crate) fn write_separated<T: Format>(items: &[T::Out], separator: &[u8], buffer: &mut String) {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            buffer.push_str(&String::from_utf8_lossy(separator));
+        }
+        T::write(item, buffer);
+    }
+}
+
 pub trait StaticStr {
     fn as_str() -> &'static str;
 }
 
+/// Declares a type standing for a string literal, e.g. `DefStaticStr!(Comma, ",")` to use as `StrSep<Comma>`.
+#[macro_export]
 macro_rules! DefStaticStr {
     ($n:ident,$s:literal) => {
         pub struct $n {}
-        impl StaticStr for $n {
+        impl $crate::parse::StaticStr for $n {
             fn as_str() -> &'static str {
                 $s
             }
         }
+    };
+}
+
+pub use crate::DefStaticStr;
+
+/// Type name without its module paths, e.g. `Vec<usize>` instead of `alloc::vec::Vec<usize>`.
+pub(crate) fn short_type_name<T: ?Sized>() -> String {
+    let mut short = String::new();
+    let mut segment = String::new();
+    for c in std::any::type_name::<T>().chars() {
+        if c.is_alphanumeric() || c == '_' || c == ':' {
+            segment.push(c);
+        } else {
+            short.push_str(segment.rsplit("::").next().unwrap_or_default());
+            segment.clear();
+            short.push(c);
+        }
     }
+    short.push_str(segment.rsplit("::").next().unwrap_or_default());
+    short
 }
 
-pub(crate) use DefStaticStr;
-#[derive(Debug, Display, PartialEq, Eq)]
-#[display(fmt = "{} {}", context, message)]
+/// A parsing failure over the span `start..=end` of the input.
+///
+/// `path` lists the combinators the failure went through, outermost first, e.g.
+/// `Seq > Couple.left > Natural<usize>`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct Error {
-    //    current_line: String,
-    context: String,
+    start: Context,
+    end: Context,
+    path: Vec<String>,
+    found: Option<String>,
     message: String,
-    line_number: usize,
 }
 
 impl Error {
-    pub fn new(context: impl Into<String>, message: impl Into<String>, line_number: usize) -> Self {
+    pub fn new(start: Context, end: Context, message: impl Into<String>) -> Self {
         Self {
-            context: context.into(),
+            start,
+            end,
+            path: vec![],
+            found: None,
             message: message.into(),
-            line_number,
         }
     }
+
+    pub fn found(mut self, found: impl AsRef<[u8]>) -> Self {
+        self.found = Some(String::from_utf8_lossy(found.as_ref()).into_owned());
+        self
+    }
+
+    /// Records that the failure happened inside the combinator `label`.
+    pub fn within(mut self, label: impl Into<String>) -> Self {
+        self.path.insert(0, label.into());
+        self
+    }
+
+    /// Outermost combinator the failure went through, if any.
+    pub fn outermost(&self) -> Option<&str> {
+        self.path.first().map(String::as_str)
+    }
+
+    /// Renames the outermost combinator, e.g. a capture slot after the field it fills.
+    pub fn relabel_outermost(mut self, label: impl Into<String>) -> Self {
+        match self.path.first_mut() {
+            Some(outermost) => *outermost = label.into(),
+            None => self.path.push(label.into()),
+        }
+        self
+    }
+
+    pub fn start(&self) -> Context {
+        self.start
+    }
+
+    pub fn end(&self) -> Context {
+        self.end
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn path(&self) -> String {
+        self.path.join(" > ")
+    }
+
+    fn found_suffix(&self) -> String {
+        match &self.found {
+            Some(found) => format!(" (found '{}')", found.escape_debug()),
+            None => String::new(),
+        }
+    }
+
+    /// Renders the error with the offending input line and a caret underline below the failing span.
+    pub fn render(&self, lines: &[impl AsRef<str>]) -> String {
+        self.render_as("error", lines)
+    }
+
+    /// Same as `render`, for a problem reported as a warning since it didn't stop the parsing.
+    pub fn render_warning(&self, lines: &[impl AsRef<str>]) -> String {
+        self.render_as("warning", lines)
+    }
+
+    fn render_as(&self, level: &str, lines: &[impl AsRef<str>]) -> String {
+        let line_number = self.start.line.to_string();
+        let gutter = " ".repeat(line_number.len());
+        let mut rendered = format!("{}: {}: {}{}\n", level, self.path(), self.message, self.found_suffix());
+        rendered += &format!("{} --> line {}, column {}\n", gutter, self.start.line, self.start.col);
+        if let Some(line) = self.start.line.checked_sub(1).and_then(|index| lines.get(index)) {
+            let line = line.as_ref();
+            // Columns are byte offsets; count only non-continuation bytes so the carets line up under chars.
+            let chars_in = |bytes: std::ops::Range<usize>| {
+                let bytes = &line.as_bytes()[bytes.start.min(line.len())..bytes.end.min(line.len())];
+                bytes.iter().filter(|b| *b & 0xC0 != 0x80).count()
+            };
+            let first = self.start.col.max(1);
+            let last = match self.end.line == self.start.line {
+                true => self.end.col.max(first),
+                false => line.len().max(first),
+            };
+            let (before, span) = (chars_in(0..first - 1), chars_in(first - 1..last).max(1));
+            rendered += &format!("{} |\n", gutter);
+            rendered += &format!("{} | {}\n", line_number, line);
+            rendered += &format!("{} | {}{}", gutter, " ".repeat(before), "^".repeat(span));
+            // Only the start line is shown, the carets running to its end when the span goes on.
+            if self.end.line > self.start.line {
+                rendered += &format!("... until line {}, column {}", self.end.line, self.end.col);
+            }
+        }
+        rendered
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {}: {}{}",
+            self.start,
+            self.path(),
+            self.message,
+            self.found_suffix()
+        )
+    }
+}
+
+/// Feeds `bytes` to `parser`, `context` being advanced over each of them.
+fn read_bytes<P: Parse>(parser: &mut P, bytes: &[u8], context: &mut Context) -> Result<(), Error> {
+    for b in bytes {
+        context.advance(b);
+        parser.read_byte(b, *context)?;
+    }
+    Ok(())
+}
+
+/// Parsing entry points of a parser value, e.g. one built at runtime like `SeqParser::new(Natural::<usize>::default(), ",")`.
+pub trait ParseValue: Parse + Sized {
+    fn parse_value(self, bytes: &[u8]) -> Result<Self::Out, Error> {
+        self.parse_value_with_context(bytes, Context::default())
+    }
+
+    /// Parses `bytes`, `context` being the position just before the first of them.
+    fn parse_value_with_context(mut self, bytes: &[u8], mut context: Context) -> Result<Self::Out, Error> {
+        read_bytes(&mut self, bytes, &mut context)?;
+        self.end(context)
+    }
+
+    /// Parses everything `reader` yields, chunk by chunk, without holding the whole input in memory.
+    fn parse_value_reader(mut self, mut reader: impl Read) -> Result<Self::Out, Error> {
+        let mut context = Context::default();
+        let mut chunk = [0; 8192];
+        loop {
+            match reader.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(n) => read_bytes(&mut self, &chunk[..n], &mut context)?,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(Error::new(context, context, e.to_string()).within("Read")),
+            }
+        }
+        self.end(context)
+    }
 }
+impl<T: Parse> ParseValue for T {}
 
 pub trait ParseExt: Parse + Default {
     fn parse(bytes: &[u8]) -> Result<Self::Out, Error> {
-        Self::parse_with_context(bytes, Context::default())
-    }
-
-    fn parse_with_context(bytes: &[u8], mut context: Context) -> Result<Self::Out, Error> {
-        let mut parser = Self::default();
-        //let mut line_number = 0;
-        let mut current_line = vec![];
-        for b in bytes {
-            if b == &0xA {
-                context.line += 1;
-                context.col = 0;
-                current_line.clear()
+        Self::default().parse_value(bytes)
+    }
+
+    /// Parses `bytes`, `context` being the position just before the first of them.
+    fn parse_with_context(bytes: &[u8], context: Context) -> Result<Self::Out, Error> {
+        Self::default().parse_value_with_context(bytes, context)
+    }
+
+    /// Parses everything `reader` yields, chunk by chunk, without holding the whole input in memory.
+    fn parse_reader(reader: impl Read) -> Result<Self::Out, Error> {
+        Self::default().parse_value_reader(reader)
+    }
+
+    /// Same as `parse` on the lines joined by newlines, streamed from them rather than copied into a single buffer.
+    fn parse_lines(lines: &[String]) -> Result<Self::Out, Error> {
+        Self::parse_reader(LinesReader::new(lines))
+    }
+}
+impl<T: Parse + Default> ParseExt for T {}
+
+/// Reader of lines as if they were joined by newlines, without a newline after the last one.
+pub struct LinesReader<'a> {
+    lines: &'a [String],
+    line: usize,
+    pos: usize,
+}
+
+impl<'a> LinesReader<'a> {
+    pub fn new(lines: &'a [String]) -> Self {
+        Self { lines, line: 0, pos: 0 }
+    }
+}
+
+impl Read for LinesReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut written = 0;
+        while written < buf.len() && self.line < self.lines.len() {
+            let line = self.lines[self.line].as_bytes();
+            if self.pos < line.len() {
+                let n = (line.len() - self.pos).min(buf.len() - written);
+                buf[written..written + n].copy_from_slice(&line[self.pos..self.pos + n]);
+                self.pos += n;
+                written += n;
             } else {
-                context.col += 1;
-                current_line.push(*b);
+                if self.line + 1 < self.lines.len() {
+                    buf[written] = b'\n';
+                    written += 1;
+                }
+                self.line += 1;
+                self.pos = 0;
             }
-            parser.read_byte(b, context)?;
         }
-        parser.end(context)
+        Ok(written)
+    }
+}
+
+/// Parser able to spawn a new one like itself, ready for another input, e.g. for each item of a `Seq`.
+pub trait Fresh: Parse {
+    fn fresh(&self) -> Self;
+}
+impl<T: Parse + Default> Fresh for T {
+    fn fresh(&self) -> Self {
+        T::default()
+    }
+}
+
+/// Parser of an item nested in a combinator, fed byte per byte as the input comes.
+/// The parser itself is only spawned from `prototype` on the first byte of the item.
+#[derive(Debug)]
+pub struct SubParser<T: Fresh> {
+    prototype: T,
+    parser: Option<T>,
+    context: Option<Context>,
+}
+
+impl<T: Parse + Default> Default for SubParser<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: Fresh> SubParser<T> {
+    pub(crate) fn new(prototype: T) -> Self {
+        Self {
+            prototype,
+            parser: None,
+            context: None,
+        }
+    }
+
+    pub(crate) fn fresh(&self) -> Self {
+        Self::new(self.prototype.fresh())
+    }
+
+    /// Starts a new item right after `context`.
+    pub(crate) fn restart(&mut self, context: Context) {
+        self.parser = None;
+        self.context = Some(context);
+    }
+
+    /// Starts a new item right before `byte`, unless one is already started.
+    pub(crate) fn begin(&mut self, byte: &u8, context: Context) {
+        self.context.get_or_insert(context.preceding(byte));
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.parser.is_none()
+    }
+
+    pub(crate) fn read_byte(&mut self, byte: &u8, context: Context) -> Result<(), Error> {
+        self.context = Some(context);
+        let prototype = &self.prototype;
+        self.parser
+            .get_or_insert_with(|| prototype.fresh())
+            .read_byte(byte, context)
+    }
+
+    /// Ends the current item, `fallback` being its context if it was never started.
+    pub(crate) fn end(&mut self, fallback: Context) -> Result<T::Out, Error> {
+        let context = self.context.take().unwrap_or(fallback);
+        let prototype = &self.prototype;
+        self.parser.take().unwrap_or_else(|| prototype.fresh()).end(context)
     }
 }
-impl<T: Parse + Default> ParseExt for T {}
 
 pub mod capture;
+pub mod class;
 pub mod couple;
 pub mod either;
+pub mod grid;
 pub mod keep;
+pub mod located;
 pub mod natural;
+pub mod one_of;
+pub mod optional;
 pub mod separator;
 pub mod seq;
 pub mod table;
+pub mod until;
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        couple::{self, Couple},
+        natural::Natural,
+        separator::{CommaSep, EmptyLineSep, LineSep, SpaceSep},
+        seq::Seq,
+        Context, Error, LinesReader, ParseExt,
+    };
+    use std::io::Read;
+
+    #[test]
+    fn it_locates_the_failing_span_and_path() {
+        let bytes = "1 2\n3 4\n5 6x\n7 8".as_bytes();
+        type Parser = Seq<Couple<Natural<usize>, SpaceSep, Natural<usize>>, LineSep>;
+        let err = Parser::parse(bytes).unwrap_err();
+        assert_eq!(err.path(), "Seq > Couple.right > Natural<usize>");
+        assert_eq!(err.start(), Context { line: 3, col: 3 });
+        assert_eq!(err.end(), Context { line: 3, col: 4 });
+    }
+
+    #[test]
+    fn it_renders_a_caret_under_the_failing_span() {
+        let input = ["1,2", "3,abc,4"];
+        let err = Seq::<Seq<Natural<usize>, CommaSep>, LineSep>::parse(input.join("\n").as_bytes()).unwrap_err();
+        let rendered = err.render(&input);
+        assert_eq!(
+            rendered.lines().skip(2).collect::<Vec<_>>(),
+            vec!["  |", "2 | 3,abc,4", "  |   ^^^"]
+        );
+
+        let input = ["éü,aé"];
+        type Parser = Couple<Natural<String>, CommaSep, Natural<usize>, couple::SplitFirst>;
+        let err = Parser::parse(input[0].as_bytes()).unwrap_err();
+        assert_eq!((err.start().col(), err.end().col()), (6, 8));
+        assert_eq!(err.render(&input).lines().last(), Some("  |    ^^"));
+    }
+
+    #[test]
+    fn it_marks_a_span_that_continues_past_its_first_line() {
+        let input = ["ab", "cd", "ef"];
+        let err = Error::new(Context { line: 1, col: 2 }, Context { line: 3, col: 2 }, "unclosed");
+        assert_eq!(
+            err.render(&input).lines().last(),
+            Some("  |  ^... until line 3, column 2")
+        );
+    }
+
+    #[test]
+    fn it_parses_from_a_reader() {
+        let input = "1,2\n3\n\n40,5\n6";
+        type Parser = Seq<Seq<Seq<Natural<usize>, CommaSep>, LineSep>, EmptyLineSep>;
+        let from_reader = Parser::parse_reader(input.as_bytes().chain("0".as_bytes())).unwrap();
+        assert_eq!(
+            from_reader,
+            vec![vec![vec![1, 2], vec![3]], vec![vec![40, 5], vec![60]]]
+        );
+
+        let err = Parser::parse_reader("1,2\n\n3,x".as_bytes()).unwrap_err();
+        assert_eq!(err.start(), Context { line: 3, col: 3 });
+    }
+
+    #[test]
+    fn it_streams_lines_as_if_they_were_joined() {
+        let lines: Vec<String> = ["1,2", "", "30", ""].map(String::from).to_vec();
+        let mut joined = vec![];
+        for size in [1, 2, 64] {
+            let (mut reader, mut buf, mut read) = (LinesReader::new(&lines), vec![0; size], vec![]);
+            while let Ok(n @ 1..) = reader.read(&mut buf) {
+                read.extend_from_slice(&buf[..n]);
+            }
+            joined.push(String::from_utf8(read).unwrap());
+        }
+        assert_eq!(joined, vec![lines.join("\n"); 3]);
+
+        type Parser = Seq<Seq<Natural<usize>, CommaSep>, LineSep>;
+        let err = Parser::parse_lines(&lines).unwrap_err();
+        assert_eq!(err, Parser::parse(lines.join("\n").as_bytes()).unwrap_err());
+    }
+}
@@ -1,5 +1,7 @@
+use super::short_type_name;
 use super::Context;
 use super::Error;
+use super::Format;
 use super::Parse;
 use std::{fmt::Display, marker::PhantomData, str::FromStr};
 
@@ -7,12 +9,14 @@ use std::{fmt::Display, marker::PhantomData, str::FromStr};
 pub struct Natural<T: FromStr> {
     p: PhantomData<T>,
     bytes: Vec<u8>,
+    start_context: Option<Context>,
 }
 impl<T: FromStr> Default for Natural<T> {
     fn default() -> Self {
         Self {
             p: Default::default(),
             bytes: vec![],
+            start_context: None,
         }
     }
     //
@@ -24,14 +28,30 @@ where
 {
     type Out = T;
 
-    fn read_byte(&mut self, byte: &u8, _context: Context) -> Result<(), Error> {
+    fn read_byte(&mut self, byte: &u8, context: Context) -> Result<(), Error> {
+        self.start_context.get_or_insert(context);
         self.bytes.push(*byte);
         Ok(())
     }
 
     fn end(self, context: Context) -> Result<Self::Out, Error> {
-        let string = std::str::from_utf8(&self.bytes).map_err(|_| Error::new("utf8 shit", "", 0))?;
-        T::from_str(string).map_err(|e| Error::new(string.to_string(), e.to_string(), context.line))
+        let start = self.start_context.unwrap_or(context);
+        let fail = |message: String| {
+            Error::new(start, context, message)
+                .found(&self.bytes)
+                .within(format!("Natural<{}>", short_type_name::<T>()))
+        };
+        let string = std::str::from_utf8(&self.bytes).map_err(|e| fail(e.to_string()))?;
+        T::from_str(string).map_err(|e| fail(e.to_string()))
+    }
+}
+
+impl<T, E: Display> Format for Natural<T>
+where
+    T: FromStr<Err = E> + Display,
+{
+    fn write(out: &T, buffer: &mut String) {
+        buffer.push_str(&out.to_string());
     }
 }
 
@@ -0,0 +1,141 @@
+use std::marker::PhantomData;
+
+use super::{Context, Error, Format, Parse, ParseExt};
+
+/// Keeps whichever of two failures went further into the input.
+pub(crate) fn furthest(current: Option<Error>, candidate: Error) -> Error {
+    match current {
+        Some(current) if (current.start(), current.end()) >= (candidate.start(), candidate.end()) => current,
+        _ => candidate,
+    }
+}
+
+/// Tuple of parsers tried in order on the same input by `OneOf`.
+pub trait Alternatives {
+    type Out;
+    fn parse_first(bytes: &[u8], context: Context) -> Result<Self::Out, Error>;
+}
+
+/// `Alternatives` whose parsers can all write their output back.
+pub trait FormatAlternatives: Alternatives {
+    fn write(out: &Self::Out, buffer: &mut String);
+}
+
+macro_rules! impl_alternatives {
+    ($alt:ident; $($p:ident $v:ident $i:tt),+) => {
+        /// Output of `OneOf`, telling which alternative succeeded.
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub enum $alt<$($v),+> {
+            $($v($v)),+
+        }
+
+        impl<$($p: Parse + Default),+> Alternatives for ($($p,)+) {
+            type Out = $alt<$($p::Out),+>;
+
+            fn parse_first(bytes: &[u8], context: Context) -> Result<Self::Out, Error> {
+                let mut error = None;
+                $(
+                    match $p::parse_with_context(bytes, context) {
+                        Ok(out) => return Ok($alt::$v(out)),
+                        Err(e) => error = Some(furthest(error, e.within(format!("OneOf.{}", $i)))),
+                    }
+                )+
+                Err(error.unwrap())
+            }
+        }
+
+        impl<$($p: Format + Default),+> FormatAlternatives for ($($p,)+) {
+            fn write(out: &Self::Out, buffer: &mut String) {
+                match out {
+                    $($alt::$v(out) => $p::write(out, buffer)),+
+                }
+            }
+        }
+    };
+}
+
+impl_alternatives!(Alt2; P0 A 0, P1 B 1);
+impl_alternatives!(Alt3; P0 A 0, P1 B 1, P2 C 2);
+impl_alternatives!(Alt4; P0 A 0, P1 B 1, P2 C 2, P3 D 3);
+impl_alternatives!(Alt5; P0 A 0, P1 B 1, P2 C 2, P3 D 3, P4 E 4);
+impl_alternatives!(Alt6; P0 A 0, P1 B 1, P2 C 2, P3 D 3, P4 E 4, P5 F 5);
+impl_alternatives!(Alt7; P0 A 0, P1 B 1, P2 C 2, P3 D 3, P4 E 4, P5 F 5, P6 G 6);
+impl_alternatives!(Alt8; P0 A 0, P1 B 1, P2 C 2, P3 D 3, P4 E 4, P5 F 5, P6 G 6, P7 H 7);
+
+/// Buffers its input then returns the output of the first parser of `P` that succeeds on it.
+/// When they all fail, the error of the one which went the furthest is reported.
+pub struct OneOf<P: Alternatives> {
+    p: PhantomData<P>,
+    buffer: Vec<u8>,
+    start_context: Option<Context>,
+}
+
+impl<P: Alternatives> Default for OneOf<P> {
+    fn default() -> Self {
+        Self {
+            p: Default::default(),
+            buffer: Default::default(),
+            start_context: Default::default(),
+        }
+    }
+}
+
+impl<P: Alternatives> Parse for OneOf<P> {
+    type Out = P::Out;
+
+    fn read_byte(&mut self, byte: &u8, context: Context) -> Result<(), Error> {
+        if self.start_context.is_none() {
+            self.start_context = Some(context.preceding(byte));
+        }
+        self.buffer.push(*byte);
+        Ok(())
+    }
+
+    fn end(self, context: Context) -> Result<Self::Out, Error> {
+        P::parse_first(&self.buffer, self.start_context.unwrap_or(context))
+    }
+}
+
+impl<P: FormatAlternatives> Format for OneOf<P> {
+    fn write(out: &Self::Out, buffer: &mut String) {
+        P::write(out, buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse::{
+        couple::Couple,
+        natural::Natural,
+        separator::{LineSep, SpaceSep},
+        seq::Seq,
+        ParseExt,
+    };
+
+    use super::*;
+
+    #[test]
+    fn it_returns_the_first_alternative_that_succeeds() {
+        type Parser = OneOf<(
+            Natural<u8>,
+            Couple<Natural<usize>, SpaceSep, Natural<usize>>,
+            Natural<String>,
+        )>;
+        let tests = [
+            ("1", Alt3::A(1)),
+            ("1 2", Alt3::B((1, 2))),
+            ("300", Alt3::C("300".to_string())),
+        ];
+        tests.into_iter().for_each(|(input, expected)| {
+            assert_eq!(Parser::parse(input.as_bytes()), Ok(expected));
+        });
+    }
+
+    #[test]
+    fn it_reports_the_alternative_that_went_furthest() {
+        type Parser = Seq<OneOf<(Natural<u8>, Couple<Natural<usize>, SpaceSep, Natural<usize>>)>, LineSep>;
+        let err = Parser::parse("1\n2 x".as_bytes()).unwrap_err();
+        assert_eq!(err.path(), "Seq > OneOf.1 > Couple.right > Natural<usize>");
+        assert_eq!((err.start().line(), err.start().col(), err.end().col()), (2, 3, 3));
+    }
+}
@@ -0,0 +1,53 @@
+use super::{Context, Error, Format, Parse, SubParser};
+
+/// `None` on an empty input, the output of `P` otherwise.
+#[derive(Debug)]
+pub struct Optional<P: Parse + Default> {
+    item: SubParser<P>,
+}
+
+impl<P: Parse + Default> Default for Optional<P> {
+    fn default() -> Self {
+        Self {
+            item: Default::default(),
+        }
+    }
+}
+
+impl<P: Parse + Default> Parse for Optional<P> {
+    type Out = Option<P::Out>;
+
+    fn read_byte(&mut self, byte: &u8, context: Context) -> Result<(), Error> {
+        self.item.begin(byte, context);
+        self.item.read_byte(byte, context).map_err(|e| e.within("Optional"))
+    }
+
+    fn end(mut self, context: Context) -> Result<Self::Out, Error> {
+        match self.item.is_empty() {
+            true => Ok(None),
+            false => self.item.end(context).map(Some).map_err(|e| e.within("Optional")),
+        }
+    }
+}
+
+impl<P: Format + Default> Format for Optional<P> {
+    fn write(out: &Self::Out, buffer: &mut String) {
+        if let Some(out) = out {
+            P::write(out, buffer)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse::{couple::Couple, natural::Natural, separator::SpaceSep, ParseExt};
+
+    use super::*;
+
+    #[test]
+    fn it_parses_an_optional_value() {
+        type Parser = Couple<Natural<String>, SpaceSep, Optional<Natural<isize>>>;
+        assert_eq!(Parser::parse("noop ".as_bytes()), Ok(("noop".to_string(), None)));
+        assert_eq!(Parser::parse("addx -3".as_bytes()), Ok(("addx".to_string(), Some(-3))));
+    }
+}
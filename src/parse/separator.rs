@@ -1,9 +1,174 @@
-use super::StaticStr;
+use std::{borrow::Cow, collections::VecDeque, fmt::Debug};
+
+use super::{Context, Error, StaticStr};
 
 pub trait Separator {
     fn as_bytes() -> &'static [u8];
 }
 
+/// State of a delimiter match after feeding it a byte.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Match {
+    /// The bytes fed so far may start a delimiter.
+    Pending,
+    /// The bytes fed so far don't start a delimiter.
+    Reject,
+    /// The bytes fed so far are a delimiter, which may go on with the next bytes.
+    Matched,
+    /// The bytes fed so far are a delimiter, which ends there.
+    Complete,
+}
+
+/// Matches the delimiter between items in `Seq` and `Couple`, fed one byte after the other.
+pub trait Matcher: Clone + Debug {
+    fn feed(&mut self, byte: &u8) -> Match;
+    /// Forgets the bytes fed so far.
+    fn reset(&mut self);
+    fn describe(&self) -> String;
+}
+
+/// Delimiter made of the given bytes, possibly chosen at runtime.
+#[derive(Debug, Clone)]
+pub struct Literal {
+    bytes: Cow<'static, [u8]>,
+    matched: usize,
+}
+
+impl Literal {
+    pub fn new(bytes: impl Into<Cow<'static, [u8]>>) -> Self {
+        Self {
+            bytes: bytes.into(),
+            matched: 0,
+        }
+    }
+}
+
+impl From<&'static str> for Literal {
+    fn from(s: &'static str) -> Self {
+        Self::new(s.as_bytes())
+    }
+}
+
+impl From<String> for Literal {
+    fn from(s: String) -> Self {
+        Self::new(s.into_bytes())
+    }
+}
+
+impl Matcher for Literal {
+    fn feed(&mut self, byte: &u8) -> Match {
+        if self.bytes.get(self.matched) == Some(byte) {
+            self.matched += 1;
+            match self.matched == self.bytes.len() {
+                true => Match::Complete,
+                false => Match::Pending,
+            }
+        } else {
+            Match::Reject
+        }
+    }
+
+    fn reset(&mut self) {
+        self.matched = 0;
+    }
+
+    fn describe(&self) -> String {
+        String::from_utf8_lossy(&self.bytes).into_owned()
+    }
+}
+
+/// Type-level delimiter of `Seq` and `Couple`, either a literal `Separator` or a variable one like `Many1<Whitespace>`.
+pub trait Delimiter {
+    type Matcher: Matcher;
+    fn matcher() -> Self::Matcher;
+}
+
+impl<S: Separator> Delimiter for S {
+    type Matcher = Literal;
+
+    fn matcher() -> Literal {
+        Literal::new(S::as_bytes())
+    }
+}
+
+pub(crate) enum Piece {
+    Byte(u8, Context),
+    Delimiter(Vec<(u8, Context)>),
+}
+
+/// Sorts the input between item bytes and delimiters, holding back bytes that may start a delimiter.
+#[derive(Debug)]
+pub(crate) struct Splitter<M: Matcher> {
+    matcher: M,
+    pending: VecDeque<(u8, Context)>,
+    matched: usize,
+}
+
+impl<M: Matcher> Splitter<M> {
+    pub(crate) fn new(mut matcher: M) -> Self {
+        matcher.reset();
+        Self {
+            matcher,
+            pending: Default::default(),
+            matched: Default::default(),
+        }
+    }
+
+    pub(crate) fn fresh(&self) -> Self {
+        Self::new(self.matcher.clone())
+    }
+
+    pub(crate) fn describe(&self) -> String {
+        self.matcher.describe()
+    }
+
+    pub(crate) fn push(
+        &mut self,
+        byte: &u8,
+        context: Context,
+        out: &mut impl FnMut(Piece) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        self.pending.push_back((*byte, context));
+        match self.matcher.feed(byte) {
+            Match::Pending => Ok(()),
+            Match::Matched => {
+                self.matched = self.pending.len();
+                Ok(())
+            }
+            Match::Complete => {
+                self.matched = self.pending.len();
+                self.split(out)
+            }
+            Match::Reject => self.split(out),
+        }
+    }
+
+    /// Releases everything still held back, as the input ended.
+    pub(crate) fn finish(&mut self, out: &mut impl FnMut(Piece) -> Result<(), Error>) -> Result<(), Error> {
+        while !self.pending.is_empty() {
+            self.split(out)?;
+        }
+        Ok(())
+    }
+
+    /// Releases the longest delimiter matched, or else the first pending byte, then goes on with the others.
+    fn split(&mut self, out: &mut impl FnMut(Piece) -> Result<(), Error>) -> Result<(), Error> {
+        let mut rest = std::mem::take(&mut self.pending);
+        self.matcher.reset();
+        if self.matched > 0 {
+            let delimiter = rest.drain(..self.matched).collect();
+            self.matched = 0;
+            out(Piece::Delimiter(delimiter))?;
+        } else if let Some((byte, context)) = rest.pop_front() {
+            out(Piece::Byte(byte, context))?;
+        }
+        for (byte, context) in rest {
+            self.push(&byte, context, out)?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 pub struct CommaSep {}
 impl Separator for CommaSep {
@@ -38,7 +203,7 @@ impl Separator for SpaceSep {
 
 #[derive(Debug)]
 pub struct StrSep<S: StaticStr> {
-    s:std::marker::PhantomData<S>
+    s: std::marker::PhantomData<S>,
 }
 
 impl<S: StaticStr> Separator for StrSep<S> {
@@ -47,10 +212,8 @@ impl<S: StaticStr> Separator for StrSep<S> {
     }
 }
 
-
 DefStaticStr!(Dash, "-");
 DefStaticStr!(Comma, ",");
 DefStaticStr!(CommaSpace, ", ");
 DefStaticStr!(Space, " ");
 DefStaticStr!(Empty, "");
-
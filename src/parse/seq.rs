@@ -1,7 +1,10 @@
-use super::{separator::Separator, Context, Error, Parse, ParseExt};
-use std::{collections::VecDeque, marker::PhantomData};
+use super::{
+    separator::{Delimiter, Literal, Matcher, Piece, Separator, Splitter},
+    write_separated, Context, Error, Format, Fresh, Parse, SubParser,
+};
+use std::marker::PhantomData;
 
-#[derive(PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EmptyBehavior {
     Skip,
     Keep,
@@ -33,62 +36,233 @@ impl EmptyBehaviorLike for SkipFinal {
     }
 }
 
-#[derive(Debug)]
-pub struct Seq<T: Parse + Default, S: Separator, EB: EmptyBehaviorLike = Keep> {
-    previous_context: Context,
-    p: PhantomData<T>,
+pub struct Seq<T: Parse + Default, S: Delimiter, EB: EmptyBehaviorLike = Keep> {
     sep: PhantomData<S>,
     empty_beh: PhantomData<EB>,
-    res: Vec<T::Out>,
-    accepted: Vec<u8>,
-    potential: VecDeque<u8>,
+    inner: SeqParser<T, S::Matcher>,
 }
 
-impl<T: Parse + Default, S: Separator, EB: EmptyBehaviorLike> Default for Seq<T, S, EB> {
+impl<T: Parse + Default, S: Delimiter, EB: EmptyBehaviorLike> Default for Seq<T, S, EB> {
     fn default() -> Self {
         Self {
-            previous_context: Default::default(),
-            p: Default::default(),
             sep: Default::default(),
             empty_beh: Default::default(),
-            res: Default::default(),
-            accepted: Default::default(),
-            potential: Default::default(),
+            inner: SeqParser::with_delimiter(T::default(), S::matcher()).empty(EB::behavior()),
         }
     }
 }
 
-impl<T: Parse + Default, S: Separator, EB: EmptyBehaviorLike> Parse for Seq<T, S, EB> {
+impl<T: Parse + Default, S: Delimiter, EB: EmptyBehaviorLike> Parse for Seq<T, S, EB> {
     type Out = Vec<T::Out>;
 
     fn read_byte(&mut self, byte: &u8, context: Context) -> Result<(), Error> {
-        self.potential.push_back(*byte);
+        self.inner.read_byte(byte, context)
+    }
 
-        if self.potential.len() == S::as_bytes().len() {
-            if self.potential.make_contiguous() == S::as_bytes() {
-                if !self.accepted.is_empty() || EB::behavior() != EmptyBehavior::Skip {
-                    let item = T::parse_with_context(&self.accepted, self.previous_context)?;
-                    self.res.push(item);
-                }
-                self.potential.clear();
-                self.accepted.clear();
-            } else if let Some(byte) = self.potential.pop_front() {
-                if self.accepted.is_empty() {
-                    self.previous_context = context
+    fn end(self, context: Context) -> Result<Self::Out, Error> {
+        self.inner.end(context)
+    }
+}
+
+impl<T: Format + Default, S: Separator, EB: EmptyBehaviorLike> Format for Seq<T, S, EB> {
+    fn write(out: &Self::Out, buffer: &mut String) {
+        write_separated::<T>(out, S::as_bytes(), buffer)
+    }
+}
+
+/// Same as `Seq`, except that items failing to parse don't stop the parsing: their errors are returned
+/// alongside the items that succeeded.
+pub struct SeqRecover<T: Parse + Default, S: Delimiter, EB: EmptyBehaviorLike = Keep> {
+    sep: PhantomData<S>,
+    empty_beh: PhantomData<EB>,
+    inner: SeqRecoverParser<T, S::Matcher>,
+}
+
+impl<T: Parse + Default, S: Delimiter, EB: EmptyBehaviorLike> Default for SeqRecover<T, S, EB> {
+    fn default() -> Self {
+        Self {
+            sep: Default::default(),
+            empty_beh: Default::default(),
+            inner: SeqParser::with_delimiter(T::default(), S::matcher())
+                .empty(EB::behavior())
+                .recover(),
+        }
+    }
+}
+
+impl<T: Parse + Default, S: Delimiter, EB: EmptyBehaviorLike> Parse for SeqRecover<T, S, EB> {
+    type Out = (Vec<T::Out>, Vec<Error>);
+
+    fn read_byte(&mut self, byte: &u8, context: Context) -> Result<(), Error> {
+        self.inner.read_byte(byte, context)
+    }
+
+    fn end(self, context: Context) -> Result<Self::Out, Error> {
+        self.inner.end(context)
+    }
+}
+
+/// Writes the items only, errors having no input to be written back from.
+impl<T: Format + Default, S: Separator, EB: EmptyBehaviorLike> Format for SeqRecover<T, S, EB> {
+    fn write((items, _): &Self::Out, buffer: &mut String) {
+        write_separated::<T>(items, S::as_bytes(), buffer)
+    }
+}
+
+/// Items parsed so far by a `Seq`, and the errors of the failing ones when recovering.
+struct Items<T: Fresh> {
+    empty_beh: EmptyBehavior,
+    item: SubParser<T>,
+    res: Vec<T::Out>,
+    errors: Option<Vec<Error>>,
+    failed: bool,
+}
+
+impl<T: Fresh> Items<T> {
+    fn fresh(&self) -> Self {
+        Self {
+            empty_beh: self.empty_beh,
+            item: self.item.fresh(),
+            res: Default::default(),
+            errors: self.errors.as_ref().map(|_| Vec::new()),
+            failed: false,
+        }
+    }
+
+    /// Records the error of the current item when recovering, the rest of the item being skipped.
+    fn check(&mut self, result: Result<(), Error>) -> Result<(), Error> {
+        match (result, &mut self.errors) {
+            (Err(e), Some(errors)) => {
+                errors.push(e.within("Seq"));
+                self.failed = true;
+                Ok(())
+            }
+            (result, _) => result.map_err(|e| e.within("Seq")),
+        }
+    }
+
+    fn end_item(&mut self, context: Context) -> Result<(), Error> {
+        if !self.failed {
+            let result = self.item.end(context).map(|item| self.res.push(item));
+            self.check(result)?;
+        }
+        self.failed = false;
+        Ok(())
+    }
+
+    fn take(&mut self, piece: Piece) -> Result<(), Error> {
+        match piece {
+            Piece::Byte(_, _) if self.failed => Ok(()),
+            Piece::Byte(byte, context) => {
+                let result = self.item.read_byte(&byte, context);
+                self.check(result)
+            }
+            Piece::Delimiter(delimiter) => {
+                let context = delimiter.last().map(|(_, context)| *context).unwrap_or_default();
+                if self.failed || !self.item.is_empty() || self.empty_beh != EmptyBehavior::Skip {
+                    self.end_item(context)?;
                 }
-                self.accepted.push(byte);
+                self.item.restart(context);
+                Ok(())
             }
         }
+    }
+
+    fn end(&mut self, context: Context) -> Result<(), Error> {
+        if self.failed || !self.item.is_empty() || self.empty_beh == EmptyBehavior::Keep {
+            self.end_item(context)?;
+        }
         Ok(())
     }
+}
+
+/// Value-level `Seq`, whose item parser and delimiter may be chosen at runtime.
+pub struct SeqParser<T: Fresh, M: Matcher = Literal> {
+    items: Items<T>,
+    splitter: Splitter<M>,
+}
+
+impl<T: Fresh> SeqParser<T, Literal> {
+    pub fn new(item: T, separator: impl Into<Literal>) -> Self {
+        Self::with_delimiter(item, separator.into())
+    }
+}
+
+impl<T: Fresh, M: Matcher> SeqParser<T, M> {
+    pub fn with_delimiter(item: T, matcher: M) -> Self {
+        Self {
+            items: Items {
+                empty_beh: EmptyBehavior::Keep,
+                item: SubParser::new(item),
+                res: Default::default(),
+                errors: None,
+                failed: false,
+            },
+            splitter: Splitter::new(matcher),
+        }
+    }
+
+    pub fn empty(mut self, empty_beh: EmptyBehavior) -> Self {
+        self.items.empty_beh = empty_beh;
+        self
+    }
+
+    /// Keeps parsing past failing items, see `SeqRecover`.
+    pub fn recover(mut self) -> SeqRecoverParser<T, M> {
+        self.items.errors = Some(Vec::new());
+        SeqRecoverParser(self)
+    }
+
+    fn finish(mut self, context: Context) -> Result<Items<T>, Error> {
+        let items = &mut self.items;
+        self.splitter.finish(&mut |piece| items.take(piece))?;
+        self.items.end(context)?;
+        Ok(self.items)
+    }
+}
 
-    fn end(mut self, _: Context) -> Result<Self::Out, Error> {
-        self.accepted.extend(self.potential.to_owned());
-        if !self.accepted.is_empty() || EB::behavior() == EmptyBehavior::Keep {
-            let item = T::parse_with_context(&self.accepted, self.previous_context)?;
-            self.res.push(item);
+impl<T: Fresh, M: Matcher> Fresh for SeqParser<T, M> {
+    fn fresh(&self) -> Self {
+        Self {
+            items: self.items.fresh(),
+            splitter: self.splitter.fresh(),
         }
-        Ok(self.res)
+    }
+}
+
+impl<T: Fresh, M: Matcher> Parse for SeqParser<T, M> {
+    type Out = Vec<T::Out>;
+
+    fn read_byte(&mut self, byte: &u8, context: Context) -> Result<(), Error> {
+        self.items.item.begin(byte, context);
+        let items = &mut self.items;
+        self.splitter.push(byte, context, &mut |piece| items.take(piece))
+    }
+
+    fn end(self, context: Context) -> Result<Self::Out, Error> {
+        Ok(self.finish(context)?.res)
+    }
+}
+
+/// Value-level `SeqRecover`, built with `SeqParser::recover`.
+pub struct SeqRecoverParser<T: Fresh, M: Matcher = Literal>(SeqParser<T, M>);
+
+impl<T: Fresh, M: Matcher> Fresh for SeqRecoverParser<T, M> {
+    fn fresh(&self) -> Self {
+        Self(self.0.fresh())
+    }
+}
+
+impl<T: Fresh, M: Matcher> Parse for SeqRecoverParser<T, M> {
+    type Out = (Vec<T::Out>, Vec<Error>);
+
+    fn read_byte(&mut self, byte: &u8, context: Context) -> Result<(), Error> {
+        self.0.read_byte(byte, context)
+    }
+
+    fn end(self, context: Context) -> Result<Self::Out, Error> {
+        let items = self.0.finish(context)?;
+        Ok((items.res, items.errors.unwrap_or_default()))
     }
 }
 
@@ -99,7 +273,9 @@ mod tests {
         seq::Skip,
     };
 
-    use super::{super::natural::Natural, super::ParseExt, Error, Seq};
+    use super::{
+        super::natural::Natural, super::ParseExt, super::ParseValue, EmptyBehavior, Error, Seq, SeqParser, SeqRecover,
+    };
 
     #[test]
     fn it_parses_a_vec_of_usize() {
@@ -159,4 +335,38 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn it_parses_with_a_separator_chosen_at_runtime() {
+        for (separator, bytes) in [(",", "1,2\n3,4"), ("\t", "1\t2\n3\t4")] {
+            let parser = SeqParser::new(SeqParser::new(Natural::<usize>::default(), separator), "\n");
+            assert_eq!(parser.parse_value(bytes.as_bytes()), Ok(vec![vec![1, 2], vec![3, 4]]));
+        }
+
+        let parser = SeqParser::new(Natural::<usize>::default(), String::from(";")).empty(EmptyBehavior::Skip);
+        assert_eq!(
+            parser.parse_value("1;;2;".as_bytes()),
+            Seq::<Natural<usize>, LineSep, Skip>::parse("1\n\n2\n".as_bytes())
+        );
+    }
+
+    #[test]
+    fn it_collects_every_failing_item() {
+        let bytes = "1\n2x\n3\n\n70000\n5".as_bytes();
+        let (numbers, errors) = SeqRecover::<Natural<u16>, LineSep>::parse(bytes).unwrap();
+        assert_eq!(numbers, vec![1, 3, 5]);
+        let located: Vec<_> = errors.iter().map(|e| (e.start().line(), e.path())).collect();
+        assert_eq!(
+            located,
+            vec![
+                (2, "Seq > Natural<u16>".to_string()),
+                (4, "Seq > Natural<u16>".to_string()),
+                (5, "Seq > Natural<u16>".to_string())
+            ]
+        );
+
+        let parser = SeqParser::new(Natural::<u16>::default(), ",").recover();
+        let (numbers, errors) = parser.parse_value("1,,3".as_bytes()).unwrap();
+        assert_eq!((numbers, errors.len()), (vec![1, 3], 1));
+    }
 }
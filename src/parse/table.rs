@@ -1,21 +1,20 @@
-use super::{separator::Separator, Context, Error, Parse, ParseExt};
+use super::{separator::Separator, write_separated, Context, Error, Format, Parse, SubParser};
 use std::marker::PhantomData;
 
-#[derive(PartialEq, Eq)]
+#[derive(PartialEq, Eq, Default)]
 enum Token {
+    #[default]
     Item,
     Separator,
 }
-impl Default for Token {
-    fn default() -> Self {
-        Token::Item
-    }
-}
 
+/// Row of cells of `N` bytes each, separated by `S`. Errors are labelled with the index of the failing cell.
 pub struct Table<const N: usize, S: Separator, T: Parse + Default> {
-    p: PhantomData<T>,
     sep: PhantomData<S>,
-    previous_context: Option<Context>,
+    cell: SubParser<T>,
+    cell_len: usize,
+    cell_start: Option<Context>,
+    separator_start: Option<Context>,
     accepted: Vec<u8>,
     curr_token: Token,
     res: Vec<T::Out>,
@@ -24,9 +23,11 @@ pub struct Table<const N: usize, S: Separator, T: Parse + Default> {
 impl<const N: usize, S: Separator, T: Parse + Default> Default for Table<N, S, T> {
     fn default() -> Self {
         Self {
-            p: Default::default(),
             sep: Default::default(),
-            previous_context: Default::default(),
+            cell: Default::default(),
+            cell_len: Default::default(),
+            cell_start: Default::default(),
+            separator_start: Default::default(),
             accepted: Default::default(),
             curr_token: Default::default(),
             res: Default::default(),
@@ -38,37 +39,45 @@ impl<const N: usize, S: Separator, T: Parse + Default> Parse for Table<N, S, T>
     type Out = Vec<T::Out>;
 
     fn read_byte(&mut self, byte: &u8, context: Context) -> Result<(), Error> {
-        let previous_context = self.previous_context.unwrap_or(context);
-        self.previous_context = Some(previous_context);
         match self.curr_token {
             Token::Item => {
-                self.accepted.push(*byte);
-                if self.accepted.len() == N {
-                    let item = T::parse_with_context(&self.accepted, previous_context)?;
+                let label = format!("Table.{}", self.res.len());
+                self.cell_start.get_or_insert(context);
+                self.separator_start = None;
+                self.cell.begin(byte, context);
+                self.cell
+                    .read_byte(byte, context)
+                    .map_err(|e| e.within(label.clone()))?;
+                self.cell_len += 1;
+                if self.cell_len == N {
+                    let item = self.cell.end(context).map_err(|e| e.within(label))?;
                     self.res.push(item);
-                    if S::as_bytes().len() > 0 {
+                    self.cell_len = 0;
+                    self.cell_start = None;
+                    if !S::as_bytes().is_empty() {
                         self.curr_token = Token::Separator;
-                        self.previous_context = None;
                     }
-
-                    self.accepted.clear();
+                    self.cell.restart(context);
                 }
                 Ok(())
             }
             Token::Separator => {
+                let separator_start = *self.separator_start.get_or_insert(context);
                 self.accepted.push(*byte);
                 if self.accepted.len() == S::as_bytes().len() {
                     if self.accepted == S::as_bytes() {
                         self.curr_token = Token::Item;
                         self.accepted.clear();
-                        self.previous_context = None;
+                        self.cell.restart(context);
                         Ok(())
                     } else {
                         Err(Error::new(
-                            std::str::from_utf8(&self.accepted).unwrap(),
-                            format!("Wrong separator, expected '{:?}'", S::as_bytes()),
-                            previous_context.line,
-                        ))
+                            separator_start,
+                            context,
+                            format!("Wrong separator, expected '{}'", String::from_utf8_lossy(S::as_bytes())),
+                        )
+                        .found(&self.accepted)
+                        .within("Table"))
                     }
                 } else {
                     Ok(())
@@ -78,36 +87,88 @@ impl<const N: usize, S: Separator, T: Parse + Default> Parse for Table<N, S, T>
     }
 
     fn end(self, context: Context) -> Result<Self::Out, Error> {
-        if self.curr_token == Token::Item && self.accepted.len() != S::as_bytes().len() {
-            Err(Error::new(
-                std::str::from_utf8(&self.accepted).unwrap(),
-                format!("Too small item in table"),
-                context.line,
-            ))
-        } else {
-            Ok(self.res)
+        // The separator start is only kept until the next cell begins, so it is left over at the end when
+        // the separator was cut short or no cell followed it.
+        match (self.cell_start, self.separator_start) {
+            (Some(cell_start), _) => Err(Error::new(
+                cell_start,
+                context,
+                format!(
+                    "Too small item in table, expected {} bytes but got {}",
+                    N, self.cell_len
+                ),
+            )
+            .within(format!("Table.{}", self.res.len()))),
+            (None, Some(separator_start)) if self.curr_token == Token::Separator => Err(Error::new(
+                separator_start,
+                context,
+                format!(
+                    "Incomplete separator, expected '{}'",
+                    String::from_utf8_lossy(S::as_bytes())
+                ),
+            )
+            .found(&self.accepted)
+            .within("Table")),
+            (None, Some(separator_start)) => {
+                Err(
+                    Error::new(separator_start, context, "Missing cell after the last separator")
+                        .within(format!("Table.{}", self.res.len())),
+                )
+            }
+            (None, None) => Ok(self.res),
         }
     }
 }
 
+impl<const N: usize, S: Separator, T: Format + Default> Format for Table<N, S, T> {
+    fn write(out: &Self::Out, buffer: &mut String) {
+        write_separated::<T>(out, S::as_bytes(), buffer)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::*;
-    use crate::parse::{natural::Natural, separator::StrSep, table::Table, StaticStr};
-
-
+    use crate::parse::{
+        natural::Natural,
+        separator::{CommaSpace, Space, StrSep},
+        table::Table,
+        ParseExt,
+    };
 
     #[test]
     fn it_parse_vec_of_usize() {
-        struct Space{}
-        impl StaticStr for Space {
-            fn as_str() -> &'static str {
-                " "
-            }
-        }
         let bytes = "LOL KIK --- LOL KIK".as_bytes();
         type Parser = Table<3, StrSep<Space>, Natural<String>>;
         let res = Parser::parse(bytes).unwrap();
-        println!("{res:?}",);
+        assert_eq!(res, vec!["LOL", "KIK", "---", "LOL", "KIK"]);
+    }
+
+    #[test]
+    fn it_rejects_a_dangling_separator() {
+        type Parser = Table<3, StrSep<Space>, Natural<String>>;
+        let err = Parser::parse("LOL KIK ".as_bytes()).unwrap_err();
+        assert_eq!(err.path(), "Table.2");
+        assert_eq!(err.start().col(), 8);
+
+        type Wide = Table<3, StrSep<CommaSpace>, Natural<String>>;
+        let err = Wide::parse("LOL, KIK,".as_bytes()).unwrap_err();
+        assert_eq!(err.path(), "Table");
+        assert_eq!(err.start().col(), 9);
+    }
+
+    #[test]
+    fn it_rejects_a_partial_last_cell() {
+        type Parser = Table<3, StrSep<Space>, Natural<String>>;
+        let err = Parser::parse("LOL KIK --".as_bytes()).unwrap_err();
+        assert_eq!(err.path(), "Table.2");
+        assert_eq!((err.start().col(), err.end().col()), (9, 10));
+    }
+
+    #[test]
+    fn it_labels_errors_with_the_failing_cell() {
+        type Parser = Table<2, StrSep<Space>, Natural<u8>>;
+        let err = Parser::parse("12 34 x6 78".as_bytes()).unwrap_err();
+        assert_eq!(err.path(), "Table.2 > Natural<u8>");
+        assert_eq!(err.start().col(), 7);
     }
 }
@@ -0,0 +1,87 @@
+use super::{
+    natural::Natural,
+    separator::{Delimiter, Piece, Splitter},
+    Context, Error, Format, Parse, SubParser,
+};
+
+/// Parses with `P` the input up to the first `S`, ignoring that delimiter and whatever follows it.
+#[derive(Debug)]
+pub struct Until<S: Delimiter, P: Parse + Default = Natural<String>> {
+    splitter: Splitter<S::Matcher>,
+    item: SubParser<P>,
+    found: bool,
+}
+
+impl<S: Delimiter, P: Parse + Default> Default for Until<S, P> {
+    fn default() -> Self {
+        Self {
+            splitter: Splitter::new(S::matcher()),
+            item: Default::default(),
+            found: Default::default(),
+        }
+    }
+}
+
+impl<S: Delimiter, P: Parse + Default> Until<S, P> {
+    fn take(item: &mut SubParser<P>, found: &mut bool, piece: Piece) -> Result<(), Error> {
+        match piece {
+            Piece::Byte(_, _) if *found => Ok(()),
+            Piece::Byte(byte, context) => item.read_byte(&byte, context),
+            Piece::Delimiter(_) => {
+                *found = true;
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<S: Delimiter, P: Parse + Default> Parse for Until<S, P> {
+    type Out = P::Out;
+
+    fn read_byte(&mut self, byte: &u8, context: Context) -> Result<(), Error> {
+        if self.found {
+            return Ok(());
+        }
+        self.item.begin(byte, context);
+        let (item, found) = (&mut self.item, &mut self.found);
+        self.splitter
+            .push(byte, context, &mut |piece| Self::take(item, found, piece))
+            .map_err(|e| e.within("Until"))
+    }
+
+    fn end(mut self, context: Context) -> Result<Self::Out, Error> {
+        let (item, found) = (&mut self.item, &mut self.found);
+        self.splitter
+            .finish(&mut |piece| Self::take(item, found, piece))
+            .map_err(|e| e.within("Until"))?;
+        self.item.end(context).map_err(|e| e.within("Until"))
+    }
+}
+
+/// Writes the output of `P` only, what followed the delimiter being lost.
+impl<S: Delimiter, P: Format + Default> Format for Until<S, P> {
+    fn write(out: &Self::Out, buffer: &mut String) {
+        P::write(out, buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse::{
+        class::{Many1, Whitespace},
+        separator::{LineSep, StrSep},
+        seq::{Seq, SkipFinal},
+        ParseExt,
+    };
+
+    use super::*;
+
+    DefStaticStr!(Hash, "#");
+
+    #[test]
+    fn it_parses_until_the_delimiter() {
+        type Parser = Seq<Until<StrSep<Hash>, Until<Many1<Whitespace>, Natural<usize>>>, LineSep, SkipFinal>;
+        let bytes = "12 # twelve\n7\n42\t\t#\n".as_bytes();
+        assert_eq!(Parser::parse(bytes), Ok(vec![12, 7, 42]));
+    }
+}
@@ -1,5 +1,11 @@
 use derive_more::Display;
-use std::fmt::Debug;
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fmt::{Debug, Display},
+    str::FromStr,
+    time::{Duration, Instant},
+};
 
 #[derive(Display, Debug)]
 pub enum ParsingError {
@@ -11,6 +17,13 @@ pub enum ParsingError {
         line: String,
     },
     Parse(crate::parse::Error),
+    #[display(
+        fmt = "{} malformed {}, first one: {}",
+        "_0.len()",
+        "if _0.len() == 1 { \"item\" } else { \"items\" }",
+        "_0[0]"
+    )]
+    Many(Vec<crate::parse::Error>),
     UnverifiedConstraint(String),
 }
 impl From<crate::parse::Error> for ParsingError {
@@ -18,6 +31,15 @@ impl From<crate::parse::Error> for ParsingError {
         Self::Parse(e)
     }
 }
+impl ParsingError {
+    /// Fails with every error collected by a `SeqRecover`, if any.
+    pub fn recovered<T>((items, errors): (T, Vec<crate::parse::Error>)) -> Result<T, Self> {
+        match errors.is_empty() {
+            true => Ok(items),
+            false => Err(Self::Many(errors)),
+        }
+    }
+}
 
 #[derive(Display, Debug)]
 pub enum SolvingError {
@@ -25,31 +47,314 @@ pub enum SolvingError {
     ExpectationUnfulfilled(String),
 }
 
+/// Answer of a part, compared exactly by the runner and rendered according to its kind.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", content = "value", rename_all = "lowercase")]
+pub enum Answer {
+    /// Wide enough to hold any signed or unsigned 64-bit answer, like the product of day 11.
+    Integer(i128),
+    Text(String),
+    /// Image drawn with characters, one string per row, e.g. the CRT screen of day 10.
+    Grid(Vec<String>),
+}
+
+impl Answer {
+    pub fn kind(&self) -> AnswerKind {
+        match self {
+            Answer::Integer(_) => AnswerKind::Integer,
+            Answer::Text(_) => AnswerKind::Text,
+            Answer::Grid(_) => AnswerKind::Grid,
+        }
+    }
+}
+
+impl std::fmt::Display for Answer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Integer(n) => write!(f, "{}", n),
+            Answer::Text(s) => write!(f, "{}", s),
+            Answer::Grid(rows) => write!(f, "{}", rows.join("\n")),
+        }
+    }
+}
+
+macro_rules! impl_integer_answer {
+    ($($t:ty),+) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(n: $t) -> Self {
+                    Answer::Integer(n as i128)
+                }
+            }
+        )+
+    };
+}
+
+impl_integer_answer!(i32, i64, isize, u32, u64, usize);
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Text(s)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Self {
+        Answer::Text(s.to_string())
+    }
+}
+
 pub trait Problem: Sized {
-    fn parse(lines: Vec<String>) -> Result<Self, ParsingError>;
-    fn part_one(&self) -> Result<String, SolvingError>;
-    fn part_two(&self) -> Result<String, SolvingError>;
+    fn parse(lines: &[String]) -> Result<Self, ParsingError>;
+
+    /// Same as `parse`, for problems whose parsing can be tuned by `params`, which the others ignore.
+    fn parse_with(lines: &[String], _params: &Params) -> Result<Self, ParsingError> {
+        Self::parse(lines)
+    }
+
+    /// Names and descriptions of the params read by `parse_with`, none by default.
+    fn params() -> Vec<(&'static str, &'static str)> {
+        vec![]
+    }
+
+    /// Problems found in the input which didn't stop its parsing, none by default.
+    fn warnings(&self) -> &[crate::parse::Error] {
+        &[]
+    }
+
+    fn part_one(&self) -> Result<Answer, SolvingError>;
+    fn part_two(&self) -> Result<Answer, SolvingError>;
+
+    /// Modes of the problem beyond its two parts, such as renderings of an answer, none by default.
+    fn extra_parts() -> Vec<ExtraPart<Self>> {
+        vec![]
+    }
+}
+
+/// Settings of a problem given on the command line as `--param <name>=<value>`.
+#[derive(Debug, Clone, Default)]
+pub struct Params(BTreeMap<String, String>);
+
+impl Params {
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0.get(name).map(String::as_str)
+    }
+
+    /// Value of `name` parsed as a `T`, `default` when it isn't given.
+    pub fn parse_or<T: FromStr>(&self, name: &str, default: T) -> Result<T, ParsingError>
+    where
+        T::Err: Display,
+    {
+        match self.get(name) {
+            None => Ok(default),
+            Some(value) => value
+                .parse()
+                .map_err(|e| ParsingError::UnverifiedConstraint(format!("Invalid --param {}={}: {}", name, value, e))),
+        }
+    }
+}
+
+impl FromIterator<(String, String)> for Params {
+    fn from_iter<I: IntoIterator<Item = (String, String)>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+/// Named mode of a problem, run with `aoc <day> <name>`.
+pub struct ExtraPart<T> {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub run: fn(&T) -> Result<Answer, SolvingError>,
+}
+
+/// Part to solve: 0 for the parsed problem, one of the two parts, or the name of an extra part.
+#[derive(Display, Debug, Clone, PartialEq, Eq)]
+pub enum Part {
+    #[display(fmt = "0")]
+    Parsed,
+    #[display(fmt = "1")]
+    One,
+    #[display(fmt = "2")]
+    Two,
+    Extra(String),
+}
+
+impl FromStr for Part {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "0" => Ok(Part::Parsed),
+            "1" => Ok(Part::One),
+            "2" => Ok(Part::Two),
+            _ if s.parse::<usize>().is_ok() => Err(format!("'{}' is not a part, numbered parts are 0, 1 and 2", s)),
+            _ => Ok(Part::Extra(s.to_string())),
+        }
+    }
+}
+
+/// Names of the parts of `T`, the extra ones following the numbered ones.
+fn part_names<T: Problem>() -> String {
+    ["0", "1", "2"]
+        .into_iter()
+        .chain(T::extra_parts().iter().map(|extra| extra.name))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Extra parts of `T` as `(name, description)`.
+fn extra_parts<T: Problem>() -> Vec<(&'static str, &'static str)> {
+    T::extra_parts()
+        .iter()
+        .map(|extra| (extra.name, extra.description))
+        .collect()
+}
+
+/// Fails with `Error::NoSuchParam` on the first of `params` that `T` doesn't declare.
+pub(crate) fn check_params<T: Problem>(params: &Params) -> Result<(), Error> {
+    let declared = T::params();
+    match params.0.keys().find(|name| !declared.iter().any(|(declared, _)| declared == name)) {
+        Some(name) => {
+            let available = match declared.is_empty() {
+                true => "none".to_string(),
+                false => declared.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(", "),
+            };
+            Err(Error::NoSuchParam(name.clone(), available))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Fails with `Error::NoSuchPart` unless `T` has `part`, so that it is known before parsing.
+pub(crate) fn check_part<T: Problem>(part: &Part) -> Result<(), Error> {
+    match part {
+        Part::Extra(name) if !T::extra_parts().iter().any(|extra| extra.name == name) => {
+            Err(Error::NoSuchPart(name.clone(), part_names::<T>()))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Solves `part` of `problem`, which must not be `Part::Parsed`.
+pub(crate) fn solve_part<T: Problem>(problem: &T, part: &Part) -> Result<Answer, Error> {
+    let run = match part {
+        Part::Parsed => unreachable!("the parsed problem is shown rather than solved"),
+        Part::One => T::part_one,
+        Part::Two => T::part_two,
+        Part::Extra(name) => {
+            T::extra_parts()
+                .into_iter()
+                .find(|extra| extra.name == name)
+                .ok_or_else(|| Error::NoSuchPart(name.clone(), part_names::<T>()))?
+                .run
+        }
+    };
+    run(problem).map_err(Error::SolverFailed)
 }
 
 #[derive(Display, Debug)]
 pub enum Error {
     #[display(fmt="Failed opening file \"{}\" : \"{}\"",_0,_1)]
     CantOpenInputFile(String,String),
+    #[display(fmt = "Failed reading input \"{}\" : \"{}\"", _0, _1)]
+    CantReadInput(String, String),
     #[display(fmt="Parsing failed: \"{}\"",_0)]
     ParsingFailed(ParsingError),
-    #[display(fmt="No solver implemented for day {}",_0)]
+    #[display(fmt = "No solver implemented for day {}, available days are {}", _0, "crate::registry::available_days()")]
     NoCorrespondingSolver(usize),
+    #[display(fmt = "No part \"{}\", available parts are {}", _0, _1)]
+    NoSuchPart(String, String),
+    #[display(fmt = "No param \"{}\", available params are {}", _0, _1)]
+    NoSuchParam(String, String),
+    #[display(fmt = "Solvers failed for days {:?}", _0)]
+    DaysFailed(Vec<usize>),
+    #[display(fmt = "Solvers failed for inputs {:?}", _0)]
+    InputsFailed(Vec<String>),
     #[display(fmt="Solver failed: \"{}\"",_0)]
     SolverFailed(SolvingError),
 }
 
-pub fn solve<T: Problem + Debug>(lines: Vec<String>, part: usize) -> Result<String, Error> {
+/// Answer of a part, along with the warnings raised while parsing the input.
+#[derive(Debug)]
+pub struct Solved {
+    pub answer: Answer,
+    pub warnings: Vec<crate::parse::Error>,
+}
+
+pub fn solve<T: Problem + Debug>(lines: &[String], part: &Part, params: &Params) -> Result<Solved, Error> {
+    check_part::<T>(part)?;
+    check_params::<T>(params)?;
+    let problem = T::parse_with(lines, params).map_err(Error::ParsingFailed)?;
+    let answer = match part {
+        Part::Parsed => Answer::Text(format!("{:#?}", problem)),
+        part => solve_part(&problem, part)?,
+    };
+    Ok(Solved {
+        answer,
+        warnings: problem.warnings().to_vec(),
+    })
+}
+
+/// Answers of both parts of a problem, with the time spent parsing it and solving each part.
+pub struct Timed {
+    pub parse: Duration,
+    pub parts: [(Result<Answer, SolvingError>, Duration); 2],
+}
+
+pub fn solve_timed<T: Problem>(lines: &[String]) -> Result<Timed, Error> {
+    let start = Instant::now();
     let problem = T::parse(lines).map_err(Error::ParsingFailed)?;
-    if part == 0 {
-        Ok(format!("{:#?}", problem))
-    } else if part == 1 {
-        problem.part_one().map_err(Error::SolverFailed)
-    } else {
-        problem.part_two().map_err(Error::SolverFailed)
+    let parse = start.elapsed();
+    let time = |part: fn(&T) -> Result<Answer, SolvingError>| {
+        let start = Instant::now();
+        let answer = part(&problem);
+        (answer, start.elapsed())
+    };
+    Ok(Timed {
+        parse,
+        parts: [time(T::part_one), time(T::part_two)],
+    })
+}
+
+/// Kind of answer a part of a day gives.
+#[derive(Display, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnswerKind {
+    #[display(fmt = "integer")]
+    Integer,
+    #[display(fmt = "text")]
+    Text,
+    #[display(fmt = "grid")]
+    Grid,
+}
+
+/// Registration of a `Problem` in the registry: each `dayNN` module exposes one as `SOLVER`.
+#[derive(Clone, Copy)]
+pub struct Solver {
+    pub day: usize,
+    pub title: &'static str,
+    /// Kind of the answers of part one and two.
+    pub answers: [AnswerKind; 2],
+    /// Names and descriptions of the extra parts.
+    pub extra_parts: fn() -> Vec<(&'static str, &'static str)>,
+    /// Names and descriptions of the params.
+    pub params: fn() -> Vec<(&'static str, &'static str)>,
+    pub solve: fn(&[String], &Part, &Params) -> Result<Solved, Error>,
+    pub timed: fn(&[String]) -> Result<Timed, Error>,
+    /// Same as `solve`, reported as JSON.
+    pub report: fn(&[String], &Part, &Params) -> serde_json::Value,
+}
+
+impl Solver {
+    pub const fn new<T: Problem + Debug + Serialize>(day: usize, title: &'static str, answers: [AnswerKind; 2]) -> Self {
+        Self {
+            day,
+            title,
+            answers,
+            extra_parts: extra_parts::<T>,
+            params: T::params,
+            solve: solve::<T>,
+            timed: solve_timed::<T>,
+            report: crate::json::report::<T>,
+        }
     }
 }
@@ -0,0 +1,51 @@
+use itertools::Itertools;
+
+use crate::problem::{Error, Solver};
+
+/// Solvers of every `dayNN` module, ordered by day, as found by the build script.
+pub const SOLVERS: &[Solver] = include!(concat!(env!("OUT_DIR"), "/solvers.rs"));
+
+pub fn find(day: usize) -> Result<&'static Solver, Error> {
+    SOLVERS
+        .iter()
+        .find(|solver| solver.day == day)
+        .ok_or(Error::NoCorrespondingSolver(day))
+}
+
+pub fn available_days() -> String {
+    SOLVERS.iter().map(|solver| solver.day).join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::problem::{Params, Part};
+
+    use super::*;
+
+    #[test]
+    fn it_registers_each_day_once_in_order() {
+        assert!(SOLVERS.iter().map(|solver| solver.day).tuple_windows().all(|(a, b)| a < b));
+        assert_eq!(find(1).unwrap().title, "Calorie Counting");
+        assert!(matches!(find(42), Err(Error::NoCorrespondingSolver(42))));
+    }
+
+    #[test]
+    fn it_validates_parts_before_parsing() {
+        assert_eq!("2".parse(), Ok(Part::Two));
+        assert!("7".parse::<Part>().is_err());
+
+        let tree = (find(7).unwrap().solve)(&[], &Part::Extra("tree".to_string()), &Params::default()).unwrap();
+        assert_eq!(tree.answer.to_string(), "- / (dir)");
+        let err = (find(7).unwrap().solve)(&[], &Part::Extra("svg".to_string()), &Params::default()).unwrap_err();
+        assert_eq!(err.to_string(), "No part \"svg\", available parts are 0, 1, 2, tree, du");
+    }
+
+    #[test]
+    fn it_rejects_params_a_day_does_not_declare() {
+        let params: Params = [("knot".to_string(), "2".to_string())].into_iter().collect();
+        let err = (find(1).unwrap().solve)(&[], &Part::One, &params).unwrap_err();
+        assert_eq!(err.to_string(), "No param \"knot\", available params are none");
+        let err = (find(9).unwrap().solve)(&[], &Part::One, &params).unwrap_err();
+        assert_eq!(err.to_string(), "No param \"knot\", available params are knots, show-knots");
+    }
+}
@@ -0,0 +1,257 @@
+use std::{fs, io, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    input::{input_file, read_input, render_parsing_errors},
+    json,
+    problem::{Answer, Error, Solver, SolvingError},
+    registry,
+};
+
+/// How answers and errors are printed.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(format!("'{}' is neither 'text' nor 'json'", s)),
+        }
+    }
+}
+
+/// Answers expected for a day, stored as `answers/<day>.toml` (or `answers/<day>.example.toml` for the example).
+#[derive(Deserialize, Default)]
+pub(crate) struct Expected {
+    part1: Option<toml::Value>,
+    part2: Option<toml::Value>,
+}
+
+impl Expected {
+    /// Answers stored in `file_name`, none when it doesn't exist.
+    pub(crate) fn read(file_name: &str) -> Result<Self, String> {
+        match fs::read_to_string(file_name) {
+            Ok(content) => toml::from_str(&content).map_err(|e| format!("{}: {}", file_name, e.message())),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("{}: {}", file_name, e)),
+        }
+    }
+
+    /// Expected answer of `part`, counted from 0. Integers are expected as such, and multi-line strings as grids.
+    pub(crate) fn part(&self, part: usize) -> Option<Answer> {
+        let value = [&self.part1, &self.part2][part].as_ref()?;
+        Some(match value {
+            toml::Value::Integer(n) => Answer::Integer((*n).into()),
+            toml::Value::String(s) if s.trim_end_matches('\n').contains('\n') => {
+                Answer::Grid(s.lines().map(String::from).collect())
+            }
+            toml::Value::String(s) => Answer::Text(s.clone()),
+            value => Answer::Text(value.to_string()),
+        })
+    }
+}
+
+/// Outcome of a part.
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Pass,
+    Unchecked,
+    Fail,
+}
+
+#[derive(Serialize)]
+struct PartRun {
+    status: Status,
+    answer: Option<Answer>,
+    expected: Option<Answer>,
+    error: Option<String>,
+    solve_us: u64,
+}
+
+/// Run of both parts of a day, cut short when it is `skipped` for lack of input or failed with an `error`.
+#[derive(Serialize)]
+struct DayRun {
+    day: usize,
+    title: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    skipped: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<Value>,
+    parse_us: Option<u64>,
+    parts: Vec<PartRun>,
+}
+
+impl DayRun {
+    fn regressed(&self) -> bool {
+        self.error.is_some() || self.parts.iter().any(|part| part.status == Status::Fail)
+    }
+
+    fn cells(&self) -> Vec<String> {
+        let mut cells = vec![self.day.to_string(), self.title.to_string()];
+        if let Some(skipped) = &self.skipped {
+            cells.push(format!("skipped: {}", skipped));
+        }
+        if let Some(error) = &self.error {
+            cells.push(format!("error: {}", error["message"].as_str().unwrap_or_default()));
+        }
+        cells.extend(self.parse_us.map(format_micros));
+        for part in &self.parts {
+            let status = match part.status {
+                Status::Pass => "pass",
+                Status::Unchecked => "????",
+                Status::Fail => "FAIL",
+            };
+            let shown = match (&part.answer, &part.expected, &part.error) {
+                (_, _, Some(error)) => error.clone(),
+                (Some(answer), Some(expected), _) if part.status == Status::Fail => {
+                    format!("{}, expected {}", summary(answer), summary(expected))
+                }
+                (Some(answer), _, _) => summary(answer),
+                (None, _, _) => String::new(),
+            };
+            cells.push(format!("{} {} ({})", status, shown, format_micros(part.solve_us)));
+        }
+        cells
+    }
+}
+
+fn format_micros(micros: u64) -> String {
+    match micros {
+        micros if micros < 10_000 => format!("{}µs", micros),
+        _ => format!("{}ms", micros / 1000),
+    }
+}
+
+/// Answer as shown in the table, grids like a CRT screen being elided.
+fn summary(answer: &Answer) -> String {
+    match answer {
+        Answer::Grid(rows) => format!("<{}x{} grid>", rows.first().map_or(0, String::len), rows.len()),
+        answer => answer.to_string(),
+    }
+}
+
+fn check(answer: Result<Answer, SolvingError>, expected: Option<Answer>, solve_us: u64) -> PartRun {
+    let (status, answer, error) = match answer {
+        Err(e) => (Status::Fail, None, Some(e.to_string())),
+        Ok(answer) => match &expected {
+            None => (Status::Unchecked, Some(answer), None),
+            Some(expected) if &answer == expected => (Status::Pass, Some(answer), None),
+            Some(_) => (Status::Fail, Some(answer), None),
+        },
+    };
+    PartRun {
+        status,
+        answer,
+        expected,
+        error,
+        solve_us,
+    }
+}
+
+/// Runs both parts of `solver`, checking them against the stored answers.
+fn run_day(solver: &Solver, example: bool, format: OutputFormat) -> DayRun {
+    let mut run = DayRun {
+        day: solver.day,
+        title: solver.title,
+        skipped: None,
+        error: None,
+        parse_us: None,
+        parts: vec![],
+    };
+    let ext = if example { ".example" } else { "" };
+    let expected = match Expected::read(&format!("answers/{}{}.toml", solver.day, ext)) {
+        Ok(expected) => expected,
+        Err(e) => {
+            run.error = Some(json!({ "kind": "invalid_answers", "message": e }));
+            return run;
+        }
+    };
+    let lines = match read_input(input_file(solver.day, example)) {
+        Ok(lines) => lines,
+        Err(e) => {
+            run.skipped = Some(e.to_string());
+            return run;
+        }
+    };
+    let timed = match (solver.timed)(&lines) {
+        Ok(timed) => timed,
+        Err(e) => {
+            if format == OutputFormat::Text {
+                render_parsing_errors(&e, &lines);
+            }
+            run.error = Some(json::error(&e));
+            return run;
+        }
+    };
+    run.parse_us = Some(json::micros(timed.parse));
+    for (part, (answer, duration)) in timed.parts.into_iter().enumerate() {
+        run.parts.push(check(answer, expected.part(part), json::micros(duration)));
+    }
+    run
+}
+
+fn print_table(runs: &[DayRun]) {
+    let header = ["Day", "Title", "Parse", "Part 1", "Part 2"].map(String::from).to_vec();
+    let rows: Vec<Vec<String>> = std::iter::once(header).chain(runs.iter().map(DayRun::cells)).collect();
+
+    // A row cut short by an error ends with its message, which shouldn't widen the column it starts in.
+    let columns = rows[0].len();
+    let widths: Vec<usize> = (0..columns)
+        .map(|i| {
+            rows.iter()
+                .filter(|row| i + 1 < row.len() || row.len() == columns)
+                .map(|row| row[i].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    for row in rows {
+        let line: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        println!("{}", line.join("  ").trim_end());
+    }
+}
+
+/// Runs every registered day and prints their answers, checked against `answers/`, with their timings, either as a
+/// table or as JSON. Fails when any answer differs from the stored one.
+pub fn run_all(example: bool, format: OutputFormat) -> Result<(), Error> {
+    let runs: Vec<DayRun> = registry::SOLVERS
+        .iter()
+        .map(|solver| run_day(solver, example, format))
+        .collect();
+    match format {
+        OutputFormat::Text => print_table(&runs),
+        OutputFormat::Json => println!("{:#}", json!(runs)),
+    }
+
+    let regressions: Vec<usize> = runs.iter().filter(|run| run.regressed()).map(|run| run.day).collect();
+    match regressions.is_empty() {
+        true => Ok(()),
+        false => Err(Error::DaysFailed(regressions)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_reads_no_answers_only_from_a_missing_file() {
+        assert_eq!(Expected::read("answers/missing.toml").unwrap().part(0), None);
+        assert_eq!(Expected::read("answers/1.example.toml").unwrap().part(0), Some(Answer::Integer(24000)));
+        assert!(matches!(Expected::read("answers"), Err(e) if e.starts_with("answers: ")));
+    }
+}
@@ -0,0 +1,29 @@
+//! Uses the library the way a dependent crate would, through `aoc_2022::prelude` only.
+
+use aoc_2022::prelude::*;
+
+DefStaticStr!(Arrow, " -> ");
+
+#[derive(Debug, Parse, PartialEq, Eq)]
+#[parse(pattern = "move {count} from {from} to {to}")]
+struct Move {
+    count: usize,
+    from: usize,
+    to: usize,
+}
+
+#[test]
+fn it_parses_with_combinators_and_derived_parsers() {
+    type Path = Seq<Natural<usize>, StrSep<Arrow>>;
+    assert_eq!(Path::parse("1 -> 2 -> 3".as_bytes()), Ok(vec![1, 2, 3]));
+
+    let err = Seq::<MoveParser, LineSep>::parse("move 1 from 2 to 3\nmove x from 1 to 2".as_bytes()).unwrap_err();
+    assert_eq!((err.start().line(), err.start().col()), (2, 6));
+}
+
+#[test]
+fn it_solves_registered_days() {
+    let solver = aoc_2022::registry::find(1).unwrap();
+    let lines = vec!["1000".to_string(), "".to_string(), "2000".to_string()];
+    assert_eq!((solver.solve)(&lines, &Part::One, &Params::default()).unwrap().answer, Answer::Integer(2000));
+}